@@ -1,12 +1,21 @@
 extern crate pointprocesses;
 
 
-use pointprocesses::temporal::cox;
-use pointprocesses::temporal::utils;
+use pointprocesses::temporal::cox::LognormalCox;
 
 fn main() {
+    let tmax = 10.0;
     let dt = 0.1;
-    let n = 101 as usize;
-    let wt = utils::simulate_brownian(dt, n);
-    println!("{:?}", wt);
+
+    println!("Brownian-driven lognormal Cox process.");
+    let model = LognormalCox::new(0.5, 0.4, dt);
+    let result = model.sample_path(tmax);
+    println!("{:?}", result.events);
+    println!("Latent intensity path: {:?}", result.intensity);
+
+    println!("Ornstein-Uhlenbeck-driven lognormal Cox process.");
+    let model = LognormalCox::with_ornstein_uhlenbeck(0.5, 0.8, 1.5, dt);
+    let result = model.sample_path(tmax);
+    println!("{:?}", result.events);
+    println!("Latent intensity path: {:?}", result.intensity);
 }
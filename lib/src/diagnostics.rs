@@ -0,0 +1,283 @@
+//! Goodness-of-fit diagnostics for temporal point process models,
+//! based on the time-rescaling theorem: if the events $t_1 < \cdots < t_N$
+//! are generated by a process with compensator
+//! $$
+//!     \Lambda(t) = \int_0^t \lambda_s ds
+//! $$
+//! then the increments $\tau_i = \Lambda(t_i) - \Lambda(t_{i-1})$ are
+//! i.i.d. $\mathrm{Exp}(1)$ random variables.
+use ndarray::prelude::*;
+
+use crate::temporal::traits::*;
+use crate::temporal::{PoissonProcess, VariablePoissonProcess, TimeChangePoissonProcess};
+use crate::temporal::hawkes::{ExpHawkes, SelfCorrecting};
+use crate::temporal::renewal::{RenewalProcess, InterArrival};
+use crate::temporal::utils::{simpson, upper_gamma_regularized, QUADRATURE_STEPS};
+use crate::error::{PointProcessError, check_times};
+
+
+/// Models whose compensator can be evaluated between the events.
+pub trait Compensator {
+    /// Compensator increments $\Lambda(t_i) - \Lambda(t_{i-1})$
+    /// of the given events, with $t_0 = 0$.
+    fn compensator_increments(&self, times: ArrayView1<f64>) -> Array1<f64>;
+}
+
+impl Compensator for PoissonProcess {
+    fn compensator_increments(&self, times: ArrayView1<f64>) -> Array1<f64> {
+        let lambda = self.intensity(0.);
+        let mut last = 0.;
+        times.mapv(|t| {
+            let incr = lambda * (t - last);
+            last = t;
+            incr
+        })
+    }
+}
+
+impl<F> Compensator for VariablePoissonProcess<F>
+where F: Fn(f64) -> f64 + Send + Sync
+{
+    /// The integral of the intensity between two events is computed with
+    /// the composite Simpson rule.
+    fn compensator_increments(&self, times: ArrayView1<f64>) -> Array1<f64> {
+        let mut last = 0.;
+        times.mapv(|t| {
+            let incr = simpson(|s| self.intensity(s), last, t, QUADRATURE_STEPS);
+            last = t;
+            incr
+        })
+    }
+}
+
+impl<F, G> Compensator for TimeChangePoissonProcess<F, G>
+where F: Fn(f64) -> f64 + Send + Sync,
+      G: Fn(f64) -> f64 + Send + Sync
+{
+    fn compensator_increments(&self, times: ArrayView1<f64>) -> Array1<f64> {
+        let mut last = self.cumulative_intensity(0.);
+        times.mapv(|t| {
+            let cum = self.cumulative_intensity(t);
+            let incr = cum - last;
+            last = cum;
+            incr
+        })
+    }
+}
+
+impl<D: InterArrival> Compensator for RenewalProcess<D> {
+    /// The compensator between two events is the cumulative hazard
+    /// $-\ln S(t_i - t_{i-1})$.
+    fn compensator_increments(&self, times: ArrayView1<f64>) -> Array1<f64> {
+        let dist = self.get_dist();
+        let mut last = None;
+        times.mapv(|t| {
+            let incr = match last {
+                Some(prev) => -dist.survival(t - prev).ln(),
+                None if self.is_stationary() => -dist.equilibrium_survival(t).ln(),
+                None => -dist.survival(t).ln()
+            };
+            last = Some(t);
+            incr
+        })
+    }
+}
+
+impl Compensator for SelfCorrecting {
+    fn compensator_increments(&self, times: ArrayView1<f64>) -> Array1<f64> {
+        let mut last = 0.;
+        let mut count = 0;
+        times.mapv(|t| {
+            let incr = self.integrated_intensity(last, t, count);
+            last = t;
+            count += 1;
+            incr
+        })
+    }
+}
+
+impl Compensator for ExpHawkes {
+    /// Closed form, using the recursive state
+    /// $S_i = \sum_{j \leq i} e^{-\beta(t_i - t_j)}$:
+    /// $$
+    ///     \Lambda(t_i) - \Lambda(t_{i-1}) =
+    ///     \lambda_0 (t_i - t_{i-1})
+    ///     + \frac{\alpha}{\beta} S_{i-1}\left(1 - e^{-\beta(t_i - t_{i-1})}\right)
+    /// $$
+    fn compensator_increments(&self, times: ArrayView1<f64>) -> Array1<f64> {
+        let lbda0 = self.get_background().intensity(0.);
+        let kernel = self.get_kernel();
+        let alpha = kernel.alpha;
+        let beta = kernel.beta;
+
+        let mut last = 0.;
+        let mut state = 0.;
+        times.mapv(|t| {
+            let decay = (-beta * (t - last)).exp();
+            let incr = lbda0 * (t - last) + alpha / beta * state * (1. - decay);
+            state = decay * state + 1.;
+            last = t;
+            incr
+        })
+    }
+}
+
+/// Statistic and p-value of a hypothesis test.
+#[derive(Debug,Clone,Copy)]
+pub struct TestResult {
+    pub statistic: f64,
+    pub p_value: f64
+}
+
+/// Data for a quantile-quantile plot of the rescaled
+/// inter-event times against the $\mathrm{Exp}(1)$ distribution.
+#[derive(Debug)]
+pub struct QQPlotData {
+    /// Quantiles of the $\mathrm{Exp}(1)$ distribution.
+    pub theoretical: Array1<f64>,
+    /// Sorted rescaled inter-event times.
+    pub empirical: Array1<f64>
+}
+
+/// Result of the time-rescaling goodness-of-fit diagnostics.
+#[derive(Debug)]
+pub struct RescalingDiagnostics {
+    /// Compensator increments $\tau_i$.
+    pub increments: Array1<f64>,
+    /// Kolmogorov-Smirnov test of the increments against $\mathrm{Exp}(1)$.
+    pub ks_test: TestResult,
+    /// Ljung-Box test for autocorrelation of the increments.
+    pub ljung_box_test: TestResult,
+    /// QQ-plot data of the increments.
+    pub qq_plot: QQPlotData
+}
+
+/// Run the time-rescaling diagnostics of the model on the given events:
+/// the compensator increments are tested against $\mathrm{Exp}(1)$
+/// with the Kolmogorov-Smirnov test, and for independence with the Ljung-Box
+/// test up to lag `lags`.
+pub fn time_rescaling_diagnostics<M>(
+    model: &M,
+    times: ArrayView1<f64>,
+    lags: usize) -> RescalingDiagnostics
+where M: Compensator
+{
+    let increments = model.compensator_increments(times);
+    let ks_test = ks_test_exp(increments.view());
+    let ljung_box_test = ljung_box_test(increments.view(), lags);
+    let qq_plot = qq_plot_exp(increments.view());
+
+    RescalingDiagnostics {
+        increments, ks_test, ljung_box_test, qq_plot
+    }
+}
+
+/// Same as [`time_rescaling_diagnostics`], returning an error if the
+/// event times are not finite, nonnegative and sorted.
+pub fn try_time_rescaling_diagnostics<M>(
+    model: &M,
+    times: ArrayView1<f64>,
+    lags: usize) -> Result<RescalingDiagnostics, PointProcessError>
+where M: Compensator
+{
+    check_times(times)?;
+    Ok(time_rescaling_diagnostics(model, times, lags))
+}
+
+/// Sorted copy of the data, using a total order so that NaN values do not panic.
+fn sorted(data: ArrayView1<f64>) -> Vec<f64> {
+    let mut values = data.to_vec();
+    values.sort_by(|a, b| a.total_cmp(b));
+    values
+}
+
+/// One-sample Kolmogorov-Smirnov test against the $\mathrm{Exp}(1)$ distribution.
+/// The p-value uses the asymptotic Kolmogorov distribution with
+/// Stephens' small-sample correction.
+pub fn ks_test_exp(data: ArrayView1<f64>) -> TestResult {
+    let n = data.len();
+    if n == 0 {
+        return TestResult { statistic: 0., p_value: 1. };
+    }
+    let nf = n as f64;
+
+    let statistic = sorted(data).iter()
+        .enumerate()
+        .fold(0., |acc: f64, (i, x)| {
+            let cdf = 1. - (-x.max(0.)).exp();
+            let d_plus = (i + 1) as f64 / nf - cdf;
+            let d_minus = cdf - i as f64 / nf;
+            acc.max(d_plus).max(d_minus)
+        });
+
+    let sqrt_n = nf.sqrt();
+    let p_value = kolmogorov_survival((sqrt_n + 0.12 + 0.11 / sqrt_n) * statistic);
+
+    TestResult { statistic, p_value }
+}
+
+/// Ljung-Box portmanteau test on the autocorrelations of the data:
+/// $$
+///     Q = n(n+2)\sum_{k=1}^h \frac{\hat\rho_k^2}{n-k}
+/// $$
+/// which is asymptotically $\chi^2_h$ under the independence hypothesis.
+/// The number of lags $h$ is capped at $n-1$.
+pub fn ljung_box_test(data: ArrayView1<f64>, lags: usize) -> TestResult {
+    let n = data.len();
+    let lags = lags.min(n.saturating_sub(1));
+    if lags == 0 {
+        return TestResult { statistic: 0., p_value: 1. };
+    }
+    let nf = n as f64;
+
+    let mean = data.sum() / nf;
+    let centered = data.mapv(|x| x - mean);
+    let var = centered.fold(0., |acc, x| acc + x * x);
+    if var == 0. {
+        return TestResult { statistic: 0., p_value: 1. };
+    }
+
+    let statistic = nf * (nf + 2.) * (1..=lags).fold(0., |acc, k| {
+        let rho = centered.iter().skip(k)
+            .zip(centered.iter())
+            .fold(0., |acc, (x, y)| acc + x * y) / var;
+        acc + rho * rho / (nf - k as f64)
+    });
+    let p_value = chi_square_survival(statistic, lags as f64);
+
+    TestResult { statistic, p_value }
+}
+
+/// QQ-plot data against the $\mathrm{Exp}(1)$ distribution, using the
+/// plotting positions $(i - 1/2)/n$.
+pub fn qq_plot_exp(data: ArrayView1<f64>) -> QQPlotData {
+    let n = data.len() as f64;
+    let empirical = Array1::from_vec(sorted(data));
+    let theoretical = Array1::from_shape_fn(empirical.len(), |i| {
+        -(1. - (i as f64 + 0.5) / n).ln()
+    });
+
+    QQPlotData { theoretical, empirical }
+}
+
+/// Survival function of the Kolmogorov distribution.
+fn kolmogorov_survival(x: f64) -> f64 {
+    if x < 0.2 {
+        return 1.;
+    }
+    let sum = (1..=100).fold(0., |acc, k| {
+        let sign = if k % 2 == 1 { 1. } else { -1. };
+        let kf = k as f64;
+        acc + sign * (-2. * kf * kf * x * x).exp()
+    });
+    (2. * sum).clamp(0., 1.)
+}
+
+/// Survival function of the $\chi^2$ distribution with `dof` degrees of freedom.
+fn chi_square_survival(x: f64, dof: f64) -> f64 {
+    if x <= 0. {
+        1.
+    } else {
+        upper_gamma_regularized(0.5 * dof, 0.5 * x)
+    }
+}
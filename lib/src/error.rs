@@ -0,0 +1,99 @@
+//! Error type for the fallible APIs of the crate.
+use std::error::Error;
+use std::fmt;
+
+use ndarray::prelude::*;
+use ndarray::ShapeError;
+
+
+/// Errors raised when building or using point process models.
+#[derive(Debug,Clone,PartialEq)]
+pub enum PointProcessError {
+    /// A model parameter is outside of its valid range.
+    InvalidParameter(String),
+    /// The model is explosive: its branching ratio is not below 1.
+    NonStationary {
+        branching_ratio: f64
+    },
+    /// The input data is invalid, e.g. contains NaN or unsorted event times.
+    InvalidData(String),
+    /// An estimator was used before being fitted.
+    NotFitted,
+    /// Arrays could not be assembled into the requested shape.
+    Shape(ShapeError)
+}
+
+impl fmt::Display for PointProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PointProcessError::InvalidParameter(msg) => {
+                write!(f, "invalid parameter: {}", msg)
+            },
+            PointProcessError::NonStationary { branching_ratio } => {
+                write!(f, "non-stationary model: branching ratio {} is not below 1",
+                       branching_ratio)
+            },
+            PointProcessError::InvalidData(msg) => {
+                write!(f, "invalid data: {}", msg)
+            },
+            PointProcessError::NotFitted => {
+                write!(f, "estimator was not fitted")
+            },
+            PointProcessError::Shape(err) => {
+                write!(f, "shape error: {}", err)
+            }
+        }
+    }
+}
+
+impl Error for PointProcessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PointProcessError::Shape(err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl From<ShapeError> for PointProcessError {
+    fn from(err: ShapeError) -> Self {
+        PointProcessError::Shape(err)
+    }
+}
+
+/// Check that a parameter is finite and positive.
+pub(crate) fn check_positive(name: &str, value: f64) -> Result<(), PointProcessError> {
+    if value.is_finite() && value > 0. {
+        Ok(())
+    } else {
+        Err(PointProcessError::InvalidParameter(
+            format!("{} must be positive and finite, got {}", name, value)))
+    }
+}
+
+/// Check that a parameter is finite and nonnegative.
+pub(crate) fn check_nonnegative(name: &str, value: f64) -> Result<(), PointProcessError> {
+    if value.is_finite() && value >= 0. {
+        Ok(())
+    } else {
+        Err(PointProcessError::InvalidParameter(
+            format!("{} must be nonnegative and finite, got {}", name, value)))
+    }
+}
+
+/// Check that event times are finite, nonnegative and sorted.
+pub fn check_times(times: ArrayView1<f64>) -> Result<(), PointProcessError> {
+    let mut last = 0.;
+    for (i, t) in times.iter().enumerate() {
+        if !t.is_finite() {
+            return Err(PointProcessError::InvalidData(
+                format!("event time {} is {}", i, t)));
+        }
+        if *t < last {
+            return Err(PointProcessError::InvalidData(
+                format!("event time {} is {}, before the previous event or 0", i, t)));
+        }
+        last = *t;
+    }
+    Ok(())
+}
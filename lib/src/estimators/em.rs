@@ -0,0 +1,328 @@
+//! Expectation-maximization estimators for Hawkes processes.
+//! The Hawkes process is a cluster process: every event is either an
+//! immigrant, generated by the background intensity, or the offspring
+//! of a previous event. Given the parameters, the posterior probability that
+//! event $i$ was triggered by event $j < i$ is
+//! $$
+//!     p_{ij} = \frac{g(t_i - t_j)}{\lambda_{t_i}},
+//!     \quad p_{ii} = \frac{\lambda_0}{\lambda_{t_i}}
+//! $$
+//! (E-step), and the parameters are then updated by maximizing
+//! the expected complete-data log-likelihood (M-step).
+use ndarray::prelude::*;
+
+use crate::temporal::hawkes::*;
+use crate::temporal::DeterministicIntensity;
+use crate::error::{PointProcessError, check_positive};
+use super::check_data;
+
+/// Relative tolerance below which the exponential kernels are truncated
+/// in the E-step.
+static KERNEL_TRUNCATION: f64 = 1e-12;
+
+
+/// Options for the EM estimators.
+#[derive(Debug,Clone)]
+pub struct EmOptions {
+    /// Maximum number of EM iterations.
+    pub max_iter: usize,
+    /// Tolerance on the relative change of the parameters.
+    pub tol: f64
+}
+
+impl Default for EmOptions {
+    fn default() -> Self {
+        EmOptions {
+            max_iter: 500,
+            tol: 1e-6
+        }
+    }
+}
+
+/// Result of an EM fit.
+#[derive(Debug)]
+pub struct EmResult<M> {
+    /// Fitted model.
+    pub model: M,
+    /// Posterior probability that each event is an immigrant.
+    pub background_probs: Array1<f64>,
+    /// Posterior branching probabilities: for each event $i$, the pairs
+    /// $(j, p_{ij})$ over the earlier events $j$ within the kernel support.
+    /// With the background probability, each row sums to 1.
+    pub parent_probs: Vec<Vec<(usize, f64)>>,
+    /// Number of EM iterations.
+    pub iterations: usize,
+    /// Whether the parameters met the convergence criterion.
+    pub converged: bool
+}
+
+/// Sufficient statistics of the E-step, for a kernel which is a sum of components
+/// $g = \sum_k g_k$.
+struct EStep {
+    /// Posterior probability that each event is an immigrant.
+    background_probs: Array1<f64>,
+    /// Posterior branching probabilities of each event, if requested.
+    parent_probs: Vec<Vec<(usize, f64)>>,
+    /// Expected number of immigrants.
+    num_immigrants: f64,
+    /// Expected number of offspring attributed to each component.
+    num_offspring: Vec<f64>,
+    /// Expected total delay $\sum_{i,j} p^k_{ij}(t_i - t_j)$ of each component.
+    total_delay: Vec<f64>
+}
+
+/// Compute the posterior branching structure.
+/// `components(t, vals)` evaluates the components of the kernel into `vals`;
+/// the kernel is neglected beyond `support`. The events are sorted, so the
+/// parents within the support of each event form a sliding window.
+/// The branching probabilities are stored if `store_parents` is set.
+fn e_step<F>(
+    times: ArrayView1<f64>,
+    lbda0: f64,
+    num_comp: usize,
+    support: f64,
+    store_parents: bool,
+    components: F) -> EStep
+where F: Fn(f64, &mut [f64])
+{
+    let n_events = times.len();
+    let mut background_probs = Array1::zeros(n_events);
+    let mut parent_probs = Vec::new();
+    let mut num_immigrants = 0.;
+    let mut num_offspring = vec![0.; num_comp];
+    let mut total_delay = vec![0.; num_comp];
+
+    let mut comp_vals = vec![0.; num_comp];
+    let mut first = 0;
+    for i in 0..n_events {
+        let ti = times[i];
+        // parents within the kernel support
+        while first < i && ti - times[first] >= support {
+            first += 1;
+        }
+
+        let lbda = (first..i).fold(lbda0, |acc, j| {
+            components(ti - times[j], &mut comp_vals);
+            acc + comp_vals.iter().sum::<f64>()
+        });
+        background_probs[i] = lbda0 / lbda;
+        num_immigrants += lbda0 / lbda;
+
+        let mut parents = Vec::new();
+        for j in first..i {
+            let dt = ti - times[j];
+            components(dt, &mut comp_vals);
+            for k in 0..num_comp {
+                let prob = comp_vals[k] / lbda;
+                num_offspring[k] += prob;
+                total_delay[k] += prob * dt;
+            }
+            if store_parents {
+                parents.push((j, comp_vals.iter().sum::<f64>() / lbda));
+            }
+        }
+        if store_parents {
+            parent_probs.push(parents);
+        }
+    }
+
+    EStep {
+        background_probs, parent_probs, num_immigrants, num_offspring, total_delay
+    }
+}
+
+/// Maximum relative change between two parameter vectors.
+fn rel_change(old: &[f64], new: &[f64]) -> f64 {
+    old.iter().zip(new.iter())
+        .fold(0., |acc: f64, (o, n)| {
+            acc.max((n - o).abs() / o.abs().max(1e-12))
+        })
+}
+
+/// Fit an exponential Hawkes model $g(t) = \alpha e^{-\beta t}$
+/// to a sequence of events observed on $[0, T]$.
+/// The M-step updates the branching ratio $\eta = \alpha/\beta$ with an
+/// edge-corrected expected offspring count, and the decay $\beta$
+/// as the inverse of the mean expected offspring delay.
+pub fn em_exp_hawkes(
+    times: ArrayView1<f64>,
+    tmax: f64,
+    options: &EmOptions) -> Result<EmResult<ExpHawkes>, PointProcessError>
+{
+    check_data(times, tmax)?;
+    let init_betas = [times.len() as f64 / tmax];
+    let res = em_sumexp_hawkes(times, tmax, &init_betas, options)?;
+    let kernel = res.model.get_kernel();
+    let lbda0 = res.model.get_background().intensity(0.);
+    let model = ExpHawkes::new(kernel.get_alphas()[0], kernel.get_betas()[0], lbda0);
+
+    Ok(EmResult {
+        model,
+        background_probs: res.background_probs,
+        parent_probs: res.parent_probs,
+        iterations: res.iterations,
+        converged: res.converged
+    })
+}
+
+/// Fit a sum-of-exponentials Hawkes model
+/// $g(t) = \sum_k \alpha_k e^{-\beta_k t}$ to a sequence of events observed
+/// on $[0, T]$, starting from the decay rates `init_betas`.
+/// Each offspring is also attributed to one of the exponentials.
+/// Returns an error if there are no events or no decay rates.
+pub fn em_sumexp_hawkes(
+    times: ArrayView1<f64>,
+    tmax: f64,
+    init_betas: &[f64],
+    options: &EmOptions) -> Result<EmResult<SumExpHawkes>, PointProcessError>
+{
+    check_data(times, tmax)?;
+    if init_betas.is_empty() {
+        return Err(PointProcessError::InvalidParameter(
+            "at least one initial decay rate is needed".to_string()));
+    }
+    for beta in init_betas.iter() {
+        check_positive("initial decay rate", *beta)?;
+    }
+    let num_exp = init_betas.len();
+    let rate = times.len() as f64 / tmax;
+    // start from a branching ratio of 1/2, spread evenly across the exponentials
+    let mut lbda0 = 0.5 * rate;
+    let mut betas = init_betas.to_vec();
+    let mut alphas: Vec<f64> = betas.iter()
+        .map(|b| 0.5 * b / num_exp as f64)
+        .collect();
+
+    let mut iterations = 0;
+    let mut converged = false;
+    let mut estep;
+    loop {
+        // the kernel is neglected once every exponential is below tolerance
+        let support = alphas.iter().zip(betas.iter())
+            .fold(0., |acc: f64, (a, b)| {
+                acc.max((a / (KERNEL_TRUNCATION * lbda0)).ln() / b)
+            });
+        let last = converged || iterations == options.max_iter;
+        estep = e_step(times, lbda0, num_exp, support.max(0.), last, |dt, vals| {
+            for (val, (a, b)) in vals.iter_mut().zip(alphas.iter().zip(betas.iter())) {
+                *val = a * (-b * dt).exp();
+            }
+        });
+        if last {
+            break;
+        }
+        iterations += 1;
+
+        let new_lbda0 = estep.num_immigrants / tmax;
+        let mut new_alphas = Vec::with_capacity(num_exp);
+        let mut new_betas = Vec::with_capacity(num_exp);
+        let suff_stats = estep.num_offspring.iter().zip(estep.total_delay.iter());
+        for (&old_beta, (&num_offspring, &total_delay)) in betas.iter().zip(suff_stats) {
+            // expected kernel mass over the observation window, per unit ratio
+            let exposure = times.iter()
+                .fold(0., |acc, tj| acc + 1. - (-old_beta * (tmax - tj)).exp());
+            let eta = num_offspring / exposure;
+            let beta = if total_delay > 0. {
+                num_offspring / total_delay
+            } else {
+                old_beta
+            };
+            new_alphas.push(eta * beta);
+            new_betas.push(beta);
+        }
+
+        let change = rel_change(&[lbda0], &[new_lbda0])
+            .max(rel_change(&alphas, &new_alphas))
+            .max(rel_change(&betas, &new_betas));
+        converged = change < options.tol;
+        lbda0 = new_lbda0;
+        alphas = new_alphas;
+        betas = new_betas;
+    }
+
+    Ok(EmResult {
+        model: SumExpHawkes::new(alphas, betas, lbda0),
+        background_probs: estep.background_probs,
+        parent_probs: estep.parent_probs,
+        iterations,
+        converged
+    })
+}
+
+/// Fit a Hawkes model with a nonparametric, piecewise-constant kernel
+/// on the bins given by `edges` to a sequence of events observed on $[0, T]$.
+/// The M-step sets the value on each bin to the expected number of
+/// offspring with a delay in the bin, divided by the total time
+/// the bin was observed after each event.
+/// Returns an error if there are no events or the bin edges are invalid.
+pub fn em_nonparametric_hawkes(
+    times: ArrayView1<f64>,
+    tmax: f64,
+    edges: &[f64],
+    options: &EmOptions) -> Result<EmResult<Hawkes<ConstBackground, PiecewiseConstKernel>>, PointProcessError>
+{
+    check_data(times, tmax)?;
+    if edges.len() < 2 || !edges.iter().all(|b| b.is_finite())
+        || !edges.windows(2).all(|w| w[0] < w[1]) {
+        return Err(PointProcessError::InvalidParameter(
+            "bin edges must be at least two finite, increasing values".to_string()));
+    }
+    let num_bins = edges.len() - 1;
+    let support = edges[num_bins];
+    let rate = times.len() as f64 / tmax;
+    // start from a branching ratio of 1/2 spread uniformly over the support
+    let mut lbda0 = 0.5 * rate;
+    let mut values = vec![0.5 / (support - edges[0]); num_bins];
+
+    // time each bin is observed after the events
+    let exposures: Vec<f64> = (0..num_bins).map(|k| {
+        times.iter().fold(0., |acc, tj| {
+            let up = edges[k+1].min(tmax - tj);
+            acc + (up - edges[k]).max(0.)
+        })
+    }).collect();
+
+    let mut iterations = 0;
+    let mut converged = false;
+    let mut estep;
+    loop {
+        let kernel = PiecewiseConstKernel::new(edges.to_vec(), values.clone());
+        let last = converged || iterations == options.max_iter;
+        estep = e_step(times, lbda0, num_bins, support, last, |dt, vals| {
+            for val in vals.iter_mut() {
+                *val = 0.;
+            }
+            if let Some(k) = kernel.bin_index(dt) {
+                vals[k] = values[k];
+            }
+        });
+        if last {
+            break;
+        }
+        iterations += 1;
+
+        let new_lbda0 = estep.num_immigrants / tmax;
+        let new_values: Vec<f64> = (0..num_bins).map(|k| {
+            if exposures[k] > 0. {
+                estep.num_offspring[k] / exposures[k]
+            } else {
+                0.
+            }
+        }).collect();
+
+        let change = rel_change(&[lbda0], &[new_lbda0])
+            .max(rel_change(&values, &new_values));
+        converged = change < options.tol;
+        lbda0 = new_lbda0;
+        values = new_values;
+    }
+
+    let kernel = PiecewiseConstKernel::new(edges.to_vec(), values);
+    Ok(EmResult {
+        model: Hawkes::with_kernel(ConstBackground::new(lbda0), kernel),
+        background_probs: estep.background_probs,
+        parent_probs: estep.parent_probs,
+        iterations,
+        converged
+    })
+}
@@ -0,0 +1,241 @@
+//! Maximum likelihood estimation of exponential Hawkes models.
+//! The log-likelihood is maximized over $(\lambda_0, \alpha, \beta)$
+//! with a projected L-BFGS algorithm, keeping the parameters positive.
+use ndarray::prelude::*;
+use rayon::prelude::*;
+
+use crate::temporal::hawkes::ExpHawkes;
+use crate::likelihood::HawkesLikelihood;
+use crate::error::PointProcessError;
+use super::check_window;
+
+
+/// Lower bound on the fitted parameters.
+static PARAM_LOWER_BOUND: f64 = 1e-10;
+/// Number of correction pairs kept by L-BFGS.
+static LBFGS_MEMORY: usize = 5;
+
+/// Options for the maximum likelihood fitting routines.
+#[derive(Debug,Clone)]
+pub struct FitOptions {
+    /// Maximum number of optimizer iterations.
+    pub max_iter: usize,
+    /// Tolerance on the relative decrease of the objective and on the
+    /// projected gradient norm.
+    pub tol: f64,
+    /// Initial guess for $(\lambda_0, \alpha, \beta)$.
+    /// If `None`, it is derived from the event rate of the data.
+    pub init: Option<[f64; 3]>
+}
+
+impl Default for FitOptions {
+    fn default() -> Self {
+        FitOptions {
+            max_iter: 200,
+            tol: 1e-8,
+            init: None
+        }
+    }
+}
+
+/// Result of a maximum likelihood fit.
+#[derive(Debug)]
+pub struct HawkesFitResult {
+    /// Fitted model.
+    pub model: ExpHawkes,
+    /// Number of optimizer iterations.
+    pub iterations: usize,
+    /// Log-likelihood of the data under the fitted model.
+    pub log_likelihood: f64,
+    /// Whether the optimizer met the convergence criterion.
+    pub converged: bool
+}
+
+/// Fit an exponential Hawkes model to a sequence of events
+/// observed on $[0, T]$ by maximum likelihood.
+/// Returns an error if there are no events, or if the event times are
+/// invalid or outside of the observation window.
+pub fn fit_exp_hawkes(
+    times: ArrayView1<f64>,
+    tmax: f64,
+    options: &FitOptions) -> Result<HawkesFitResult, PointProcessError>
+{
+    fit_exp_hawkes_multi(&[(times, tmax)], options)
+}
+
+/// Fit an exponential Hawkes model by maximum likelihood
+/// to independent sequences of events, each with its own observation window.
+/// The pooled log-likelihood is the sum of the log-likelihoods of the sequences.
+/// Returns an error if the sequences hold no events, or if the event times
+/// of a sequence are invalid or outside of its observation window.
+pub fn fit_exp_hawkes_multi(
+    sequences: &[(ArrayView1<f64>, f64)],
+    options: &FitOptions) -> Result<HawkesFitResult, PointProcessError>
+{
+    for (times, tmax) in sequences.iter() {
+        check_window(*times, *tmax)?;
+    }
+    if sequences.iter().all(|(times, _)| times.is_empty()) {
+        return Err(PointProcessError::InvalidData(
+            "cannot fit a model to sequences without events".to_string()));
+    }
+
+    // negative pooled log-likelihood and its gradient,
+    // with the sequences evaluated in parallel
+    let objective = |x: &[f64; 3]| -> (f64, [f64; 3]) {
+        sequences.par_iter()
+            .map(|(times, tmax)| {
+                let hl_obj = HawkesLikelihood::new(
+                    *times, x[0], x[1], x[2], *tmax);
+                let grad = hl_obj.grad();
+                (-hl_obj.compute_likelihood(), [-grad[0], -grad[1], -grad[2]])
+            })
+            .reduce(|| (0., [0.; 3]), |(f, g), (f2, g2)| {
+                (f + f2, [g[0] + g2[0], g[1] + g2[1], g[2] + g2[2]])
+            })
+    };
+
+    let init = options.init.unwrap_or_else(|| {
+        // start from a branching ratio of 1/2 at the observed event rate
+        let (num_events, total_time) = sequences.iter()
+            .fold((0., 0.), |(n, t), (times, tmax)| {
+                (n + times.len() as f64, t + tmax)
+            });
+        let rate = (num_events / total_time).max(PARAM_LOWER_BOUND);
+        [0.5 * rate, 0.5 * rate, rate]
+    });
+
+    let (x, fx, iterations, converged) = minimize_projected_lbfgs(
+        objective, init, options);
+
+    Ok(HawkesFitResult {
+        model: ExpHawkes::new(x[1], x[2], x[0]),
+        iterations,
+        log_likelihood: -fx,
+        converged
+    })
+}
+
+fn project(x: &mut [f64; 3]) {
+    for xi in x.iter_mut() {
+        *xi = xi.max(PARAM_LOWER_BOUND);
+    }
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a.iter().zip(b.iter()).fold(0., |acc, (u, v)| acc + u * v)
+}
+
+/// Minimize `f` over the positive orthant with a projected L-BFGS algorithm,
+/// `f` returning the objective value and its gradient.
+/// Variables at their bound with a gradient pushing outwards are held fixed,
+/// and the L-BFGS direction is computed over the free variables.
+/// Returns the minimizer, the minimum, the number of iterations and whether
+/// the algorithm converged.
+fn minimize_projected_lbfgs<F>(
+    f: F,
+    init: [f64; 3],
+    options: &FitOptions) -> ([f64; 3], f64, usize, bool)
+where F: Fn(&[f64; 3]) -> (f64, [f64; 3])
+{
+    let mut x = init;
+    project(&mut x);
+    let (mut fx, mut grad) = f(&x);
+
+    // correction pairs (s_k, y_k)
+    let mut memory: Vec<([f64; 3], [f64; 3])> = Vec::new();
+
+    for iter in 0..options.max_iter {
+        // variables held at the bound
+        let active: Vec<bool> = (0..3).map(|i| {
+            x[i] <= PARAM_LOWER_BOUND && grad[i] > 0.
+        }).collect();
+        let mut pgrad = grad;
+        for i in 0..3 {
+            if active[i] {
+                pgrad[i] = 0.;
+            }
+        }
+        let pgrad_norm = dot(&pgrad, &pgrad).sqrt();
+        if pgrad_norm < options.tol {
+            return (x, fx, iter, true);
+        }
+
+        // L-BFGS two-loop recursion
+        let mut dir = pgrad;
+        let mut alphas = Vec::with_capacity(memory.len());
+        for (s, y) in memory.iter().rev() {
+            let rho = 1. / dot(y, s);
+            let a = rho * dot(s, &dir);
+            for i in 0..3 {
+                dir[i] -= a * y[i];
+            }
+            alphas.push((a, rho));
+        }
+        let gamma = match memory.last() {
+            Some((s, y)) => dot(s, y) / dot(y, y),
+            None => 1. / pgrad_norm.max(1.)
+        };
+        for d in dir.iter_mut() {
+            *d *= gamma;
+        }
+        for ((s, y), (a, rho)) in memory.iter().zip(alphas.iter().rev()) {
+            let b = rho * dot(y, &dir);
+            for i in 0..3 {
+                dir[i] += s[i] * (a - b);
+            }
+        }
+        for i in 0..3 {
+            dir[i] = if active[i] { 0. } else { -dir[i] };
+        }
+        if dot(&dir, &grad) >= 0. {
+            // not a descent direction: restart from steepest descent
+            memory.clear();
+            for i in 0..3 {
+                dir[i] = -pgrad[i] / pgrad_norm.max(1.);
+            }
+        }
+
+        // backtracking line search along the projected path
+        let mut step = 1.;
+        let mut accepted = None;
+        for _ in 0..40 {
+            let mut x_new = x;
+            for i in 0..3 {
+                x_new[i] += step * dir[i];
+            }
+            project(&mut x_new);
+            let (f_new, grad_new) = f(&x_new);
+            let dx = [x_new[0] - x[0], x_new[1] - x[1], x_new[2] - x[2]];
+            if f_new.is_finite() && f_new <= fx + 1e-4 * dot(&grad, &dx) {
+                accepted = Some((x_new, f_new, grad_new, dx));
+                break;
+            }
+            step *= 0.5;
+        }
+
+        let (x_new, f_new, grad_new, dx) = match accepted {
+            Some(res) => res,
+            // no progress can be made along the search direction
+            None => return (x, fx, iter + 1, false)
+        };
+
+        let dg = [grad_new[0] - grad[0], grad_new[1] - grad[1], grad_new[2] - grad[2]];
+        if dot(&dx, &dg) > 1e-12 {
+            memory.push((dx, dg));
+            if memory.len() > LBFGS_MEMORY {
+                memory.remove(0);
+            }
+        }
+
+        let decrease = fx - f_new;
+        x = x_new;
+        fx = f_new;
+        grad = grad_new;
+        if decrease <= options.tol * fx.abs().max(1.) {
+            return (x, fx, iter + 1, true);
+        }
+    }
+
+    (x, fx, options.max_iter, false)
+}
@@ -1,3 +1,33 @@
 //! Estimators for point process models.
 pub mod kernels;
-pub mod nadarayawatson;
\ No newline at end of file
+pub mod nadarayawatson;
+pub mod mle;
+pub mod em;
+pub mod wienerhopf;
+
+use ndarray::prelude::*;
+
+use crate::error::{PointProcessError, check_positive, check_times};
+
+
+/// Check the event times and that they lie within the observation window $[0, T]$.
+pub(crate) fn check_window(times: ArrayView1<f64>, tmax: f64) -> Result<(), PointProcessError> {
+    check_times(times)?;
+    check_positive("tmax", tmax)?;
+    match times.iter().last() {
+        Some(last) if *last > tmax => Err(PointProcessError::InvalidData(
+            format!("event time {} is after the end of the window {}", last, tmax))),
+        _ => Ok(())
+    }
+}
+
+/// Check the event times and observation window, and that there are events
+/// to fit the model to.
+pub(crate) fn check_data(times: ArrayView1<f64>, tmax: f64) -> Result<(), PointProcessError> {
+    check_window(times, tmax)?;
+    if times.is_empty() {
+        return Err(PointProcessError::InvalidData(
+            "cannot fit a model to an empty sequence of events".to_string()));
+    }
+    Ok(())
+}
@@ -3,6 +3,7 @@
 use ndarray::prelude::*;
 
 use super::kernels::*;
+use crate::error::PointProcessError;
 
 
 /// Nadaraya-Watson nonparametric estimator for functions using
@@ -35,8 +36,15 @@ impl<T: RegKernel> NadWatEstimator<T> {
     /// Perform prediction at `x0`.
     pub fn predict(&self, x0: f64) -> f64
     {
-        let x_arr: &Array1<f64> = self.x_i.as_ref().expect("Regressor was not fitted.");
-        let y_arr: &Array1<f64> = self.y_i.as_ref().expect("Regressor was not fitted.");
+        self.try_predict(x0).expect("Regressor was not fitted.")
+    }
+
+    /// Perform prediction at `x0`, returning an error if
+    /// the regressor was not fitted.
+    pub fn try_predict(&self, x0: f64) -> Result<f64, PointProcessError>
+    {
+        let x_arr: &Array1<f64> = self.x_i.as_ref().ok_or(PointProcessError::NotFitted)?;
+        let y_arr: &Array1<f64> = self.y_i.as_ref().ok_or(PointProcessError::NotFitted)?;
 
         let kernel = &self.kernel;
 
@@ -52,7 +60,7 @@ impl<T: RegKernel> NadWatEstimator<T> {
             }
         );
 
-        numerator / denom
+        Ok(numerator / denom)
     }
 }
 
@@ -0,0 +1,151 @@
+//! Nonparametric estimation of the Hawkes kernel by solving the
+//! Wiener-Hopf equation (Bacry & Muzy, 2016).
+//! The conditional expected density of the process
+//! $$
+//!     \nu(t) = \frac{\mathbb E[dN_{s+t} \mid dN_s = 1]}{dt} - \Lambda,
+//!     \quad t > 0
+//! $$
+//! where $\Lambda$ is the mean intensity, is related to the kernel by
+//! $$
+//!     \nu(t) = g(t) + \int_0^\infty g(s)\nu(t - s) ds
+//! $$
+//! with $\nu(-t) = \nu(t)$. The density $\nu$ is estimated from the data
+//! by a histogram, and the equation is then solved for $g$.
+use ndarray::prelude::*;
+
+use crate::temporal::hawkes::PiecewiseConstKernel;
+use crate::error::{PointProcessError, check_positive};
+use super::check_window;
+
+
+/// Estimate the Hawkes kernel of a sequence of events observed on $[0, T]$,
+/// on `num_bins` bins of width `bin_width` starting at 0.
+/// The kernel is assumed to vanish beyond the last bin.
+///
+/// The Wiener-Hopf equation is discretized by approximating
+/// both $\nu$ and $g$ as constant on each bin. The estimated values
+/// are not constrained, and can be slightly negative where the
+/// kernel is small.
+/// Returns an error if the parameters or event times are invalid,
+/// or if the discretized equation cannot be solved.
+pub fn wiener_hopf_kernel(
+    times: ArrayView1<f64>,
+    tmax: f64,
+    bin_width: f64,
+    num_bins: usize) -> Result<PiecewiseConstKernel, PointProcessError>
+{
+    check_window(times, tmax)?;
+    check_positive("bin_width", bin_width)?;
+    if num_bins == 0 {
+        return Err(PointProcessError::InvalidParameter(
+            "at least one bin is needed".to_string()));
+    }
+
+    let density = conditional_density(times, tmax, bin_width, num_bins);
+
+    // integral of nu(t_k - s) for s in bin l
+    // with t_k the middle of bin k
+    let cond_mass = |k: usize, l: usize| {
+        let lag = k.abs_diff(l);
+        if lag == 0 {
+            bin_width * density[0]
+        } else {
+            0.5 * bin_width * (density[lag - 1] + density[lag])
+        }
+    };
+
+    let mut matrix = Array2::from_shape_fn((num_bins, num_bins), |(k, l)| {
+        cond_mass(k, l)
+    });
+    for k in 0..num_bins {
+        matrix[[k, k]] += 1.;
+    }
+    let values = solve_linear_system(matrix, density)?;
+
+    let edges = (0..=num_bins).map(|k| k as f64 * bin_width).collect();
+    PiecewiseConstKernel::try_new(edges, values.to_vec())
+}
+
+/// Histogram estimate of the conditional expected density $\nu$.
+/// Only the pairs of events whose bin lies within the observation window
+/// from the first event are counted, to avoid edge effects.
+fn conditional_density(
+    times: ArrayView1<f64>,
+    tmax: f64,
+    bin_width: f64,
+    num_bins: usize) -> Array1<f64>
+{
+    let mean_intensity = times.len() as f64 / tmax;
+
+    let mut counts = Array1::<f64>::zeros(num_bins);
+    // number of events from which each bin is fully observed
+    let mut num_origins = Array1::<f64>::zeros(num_bins);
+
+    for (i, ti) in times.iter().enumerate() {
+        for k in 0..num_bins {
+            if ti + (k + 1) as f64 * bin_width <= tmax {
+                num_origins[k] += 1.;
+            }
+        }
+        for tj in times.iter().skip(i + 1) {
+            // bound the bin index itself, as comparing the lag with
+            // `num_bins * bin_width` can round differently
+            let k = ((tj - ti) / bin_width) as usize;
+            if k >= num_bins {
+                break;
+            }
+            if ti + (k + 1) as f64 * bin_width <= tmax {
+                counts[k] += 1.;
+            }
+        }
+    }
+
+    Array1::from_shape_fn(num_bins, |k| {
+        if num_origins[k] > 0. {
+            counts[k] / (num_origins[k] * bin_width) - mean_intensity
+        } else {
+            0.
+        }
+    })
+}
+
+/// Solve the linear system $Ax = b$ by Gaussian elimination
+/// with partial pivoting. Returns an error if the system is singular
+/// or its solution is not finite.
+fn solve_linear_system(mut a: Array2<f64>, mut b: Array1<f64>) -> Result<Array1<f64>, PointProcessError> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col + 1..n).fold(col, |best, i| {
+            if a[[i, col]].abs() > a[[best, col]].abs() { i } else { best }
+        });
+        if !a[[pivot, col]].is_finite() || a[[pivot, col]] == 0. {
+            return Err(PointProcessError::InvalidData(
+                "the discretized Wiener-Hopf system is singular".to_string()));
+        }
+        if pivot != col {
+            for k in 0..n {
+                a.swap([col, k], [pivot, k]);
+            }
+            b.swap(col, pivot);
+        }
+        for row in col + 1..n {
+            let factor = a[[row, col]] / a[[col, col]];
+            for k in col..n {
+                a[[row, k]] -= factor * a[[col, k]];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = Array1::zeros(n);
+    for row in (0..n).rev() {
+        let tail = (row + 1..n).fold(0., |acc, k| acc + a[[row, k]] * x[k]);
+        x[row] = (b[row] - tail) / a[[row, row]];
+    }
+    if x.iter().all(|v| v.is_finite()) {
+        Ok(x)
+    } else {
+        Err(PointProcessError::InvalidData(
+            "the solution of the Wiener-Hopf system is not finite".to_string()))
+    }
+}
@@ -4,6 +4,10 @@ pub mod spatial;
 pub mod likelihood;
 pub mod temporal;
 pub mod estimators;
+pub mod diagnostics;
+pub mod error;
+
+pub use self::error::PointProcessError;
 
 /// Time-dependent processes should be available in the crate root.
 pub use self::temporal::*;
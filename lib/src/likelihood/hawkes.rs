@@ -5,8 +5,14 @@ use ndarray::prelude::*;
 use ndarray_parallel::prelude::*;
 
 
-use crate::temporal::hawkes::ExpHawkes;
+use rayon::prelude::*;
+
+use crate::temporal::hawkes::{Hawkes, ConstBackground, Kernel, PiecewiseConstKernel};
+use crate::temporal::hawkes::{ExpHawkes, SumExpHawkes, PowerLawHawkes, TabulatedHawkes, SelfCorrecting};
+use crate::temporal::etas::{Etas, check_catalog};
+use crate::error::PointProcessError;
 use crate::temporal::DeterministicIntensity;
+use super::Likelihood;
 
 pub struct HawkesExpParams {
     lbda0: f64,
@@ -64,7 +70,6 @@ fn compute_part_sums(
     let mut r_arr = Array1::zeros(n_events);
     let mut decay;
 
-    r_arr[0] = 0.;
     for i in 1..n_events {
         decay = (-beta*(times[i] - times[i-1])).exp();
         r_arr[i] = decay * r_arr[i-1] + decay;
@@ -77,6 +82,7 @@ fn compute_part_sums(
 /// $$
 ///     C_i = \sum_{j < i} t_j e^{-\beta (t_i - t_j)}
 /// $$
+/// using the recursion $C_i = e^{-\beta(t_i - t_{i-1})}(C_{i-1} + t_{i-1})$.
 fn compute_partial_deriv_sum(
     times: ArrayView1<f64>,
     beta: f64) -> Array1<f64>
@@ -86,8 +92,8 @@ fn compute_partial_deriv_sum(
     let mut decay;
 
     for i in 1..n_events {
-        decay = (-beta * times[i+1] - times[i]).exp();
-        c_arr[i] = decay * c_arr[i-1] + times[i] * decay;
+        decay = (-beta * (times[i] - times[i-1])).exp();
+        c_arr[i] = decay * (c_arr[i-1] + times[i-1]);
     }
 
     c_arr
@@ -131,19 +137,32 @@ impl<'a> HawkesLikelihood<'a> {
 
         evt_llhood - self.integral
     }
-    
+
+    /// Gradient of the log-likelihood with respect to $(\lambda_0, \alpha, \beta)$:
+    /// $$
+    ///     \frac{\partial\ell}{\partial\lambda_0} = \sum_i \frac{1}{\lambda_{t_i}} - T,
+    ///     \quad
+    ///     \frac{\partial\ell}{\partial\alpha} = \sum_i \frac{R_i}{\lambda_{t_i}} - M,
+    /// $$
+    /// $$
+    ///     \frac{\partial\ell}{\partial\beta} = -\alpha\sum_i \frac{B_i}{\lambda_{t_i}}
+    ///     + \frac{\alpha}{\beta} M
+    ///     - \frac{\alpha}{\beta}\sum_i (T - t_i)e^{-\beta(T-t_i)}
+    /// $$
+    /// where $B_i = t_i R_i - C_i = \sum_{j<i} (t_i - t_j)e^{-\beta(t_i - t_j)}$
+    /// and $M = \sum_i \frac{1}{\beta}(1 - e^{-\beta(T-t_i)})$.
     pub fn grad(&self) -> Array1<f64> {
         let HawkesExpParams {lbda0, alpha, beta} = self.params;
         let tmax = self.tmax;
 
         // Sum pf exp(-beta(t_i-t_j))
-        let ref part_sums = self.partial_sums;
+        let part_sums = &self.partial_sums;
 
 
         let c_arr = compute_partial_deriv_sum(
             self.times, beta);
-        
-        let b_arr = self.times.to_owned() * part_sums.clone() - c_arr;
+
+        let b_arr = self.times.to_owned() * part_sums - c_arr;
 
 
         let lbda0_deriv = part_sums.iter()
@@ -151,10 +170,16 @@ impl<'a> HawkesLikelihood<'a> {
                 acc + 1. / (lbda0 + alpha * r)
             }) - tmax;
 
+        // integral of the kernels over the time window, divided by alpha
+        let kernel_mass = self.times.iter()
+            .fold(0., |acc, ti| {
+                acc + (1. - (-beta * (tmax - ti)).exp()) / beta
+            });
+
         let alpha_deriv = part_sums.iter()
             .fold(0., |acc, r| {
                 acc + r / (lbda0 + alpha * r)
-            }) - self.integral / alpha;
+            }) - kernel_mass;
 
         let integral_beta_deriv = self.times.iter()
             .fold(0., |acc, ti| {
@@ -165,9 +190,9 @@ impl<'a> HawkesLikelihood<'a> {
         let beta_deriv = part_sums.iter()
             .zip(b_arr.iter())
             .fold(0., |acc, (r, b)| {
-                acc + (-b)/(lbda0 + alpha * r)
+                acc - alpha * b / (lbda0 + alpha * r)
             })
-            + 1./beta * self.integral
+            + alpha / beta * kernel_mass
             - integral_beta_deriv;
 
         arr1(&[lbda0_deriv, alpha_deriv, beta_deriv])
@@ -204,3 +229,266 @@ pub fn hawkes_likelihood(
     hl_obj.compute_likelihood()
 }
 
+/// Log-likelihood of the given event data under the sum-of-exponentials Hawkes model.
+/// $$
+///     \ell =
+///     \sum_{i=1}^N \log\left(
+///         \lambda_0 + \sum_{k=1}^p \alpha_k R^k_i
+///     \right)
+///     - \lambda_0 T
+///     - \sum_{k=1}^p \sum_{i=1}^N \frac{\alpha_k}{\beta_k}
+///     \left(1 - e^{-\beta_k(T - t_i)}\right)
+/// $$
+/// where $R^k_i = \sum_{j < i} e^{-\beta_k(t_i-t_j)}$ is computed recursively
+/// for each exponential.
+pub fn sumexp_hawkes_likelihood(
+    times: ArrayView1<f64>,
+    model: &SumExpHawkes,
+    tmax: f64) -> f64
+{
+    let lbda0 = model.get_background().intensity(0.);
+    let kernel = model.get_kernel();
+    let alphabetazip = kernel.get_alphas().iter()
+        .zip(kernel.get_betas().iter());
+
+    let mut intensities = Array1::from_elem(times.len(), lbda0);
+    let mut integral = lbda0 * tmax;
+    for (&alpha, &beta) in alphabetazip {
+        let partial_sums = compute_part_sums(times, beta);
+        intensities.scaled_add(alpha, &partial_sums);
+        integral += integral_term(times, 0., alpha, beta, tmax);
+    }
+
+    let evt_llhood = intensities.fold(0., |acc, lbda| {
+        acc + lbda.ln()
+    });
+
+    evt_llhood - integral
+}
+
+impl Likelihood for ExpHawkes {
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64 {
+        hawkes_likelihood(times, self, tmax)
+    }
+
+    /// Gradient with respect to $(\lambda_0, \alpha, \beta)$.
+    fn grad(&self, times: ArrayView1<f64>, tmax: f64) -> Option<Array1<f64>> {
+        let lbda0 = self.get_background().intensity(0.);
+        let kernel = self.get_kernel();
+        let hl_obj = HawkesLikelihood::new(
+            times, lbda0, kernel.alpha, kernel.beta, tmax);
+        Some(hl_obj.grad())
+    }
+}
+
+impl Likelihood for SumExpHawkes {
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64 {
+        sumexp_hawkes_likelihood(times, self, tmax)
+    }
+
+    /// Gradient with respect to
+    /// $(\lambda_0, \alpha_1,\ldots,\alpha_p, \beta_1,\ldots,\beta_p)$,
+    /// each exponential contributing as in the single exponential case.
+    fn grad(&self, times: ArrayView1<f64>, tmax: f64) -> Option<Array1<f64>> {
+        let lbda0 = self.get_background().intensity(0.);
+        let kernel = self.get_kernel();
+        let alphas = kernel.get_alphas();
+        let betas = kernel.get_betas();
+        let num_exp = kernel.get_num_exp();
+
+        let part_sums: Vec<Array1<f64>> = betas.iter()
+            .map(|beta| compute_part_sums(times, *beta))
+            .collect();
+        let mut intensities = Array1::from_elem(times.len(), lbda0);
+        for (alpha, r_arr) in alphas.iter().zip(part_sums.iter()) {
+            intensities.scaled_add(*alpha, r_arr);
+        }
+
+        let mut grad = Array1::zeros(1 + 2 * num_exp);
+        grad[0] = intensities.fold(0., |acc, lbda| acc + 1. / lbda) - tmax;
+
+        for k in 0..num_exp {
+            let (alpha, beta) = (alphas[k], betas[k]);
+            let r_arr = &part_sums[k];
+            let c_arr = compute_partial_deriv_sum(times, beta);
+            let b_arr = times.to_owned() * r_arr - c_arr;
+
+            let kernel_mass = times.fold(0., |acc, ti| {
+                acc + (1. - (-beta * (tmax - ti)).exp()) / beta
+            });
+            let integral_beta_deriv = times.fold(0., |acc, ti| {
+                acc + alpha / beta * (tmax - ti) * (-beta * (tmax - ti)).exp()
+            });
+
+            grad[1 + k] = r_arr.iter()
+                .zip(intensities.iter())
+                .fold(0., |acc, (r, lbda)| acc + r / lbda)
+                - kernel_mass;
+            grad[1 + num_exp + k] = b_arr.iter()
+                .zip(intensities.iter())
+                .fold(0., |acc, (b, lbda)| acc - alpha * b / lbda)
+                + alpha / beta * kernel_mass
+                - integral_beta_deriv;
+        }
+
+        Some(grad)
+    }
+}
+
+/// Log-likelihood of the given event data under a Hawkes model with
+/// constant background intensity and any kernel.
+/// $$
+///     \ell =
+///     \sum_{i=1}^N \log\left(
+///         \lambda_0 + \sum_{j < i} g(t_i - t_j)
+///     \right)
+///     - \lambda_0 T
+///     - \sum_{i=1}^N \int_0^{T - t_i} g(s) ds
+/// $$
+/// The intensity at each event sums over the previous events within the
+/// support of the kernel, so the cost is quadratic in the number of events
+/// for kernels with infinite support.
+pub fn kernel_hawkes_likelihood<K>(
+    times: ArrayView1<f64>,
+    model: &Hawkes<ConstBackground, K>,
+    tmax: f64) -> f64
+where K: Kernel + Sync
+{
+    let lbda0 = model.get_background().intensity(0.);
+    let kernel = model.get_kernel();
+    let support = kernel.support();
+
+    let evt_llhood: f64 = (0..times.len())
+        .into_par_iter()
+        .map(|i| {
+            let ti = times[i];
+            let lbda = times.iter()
+                .take(i)
+                .rev()
+                .take_while(|tj| ti - *tj < support)
+                .fold(lbda0, |acc, tj| acc + kernel.eval(ti - tj));
+            lbda.ln()
+        }).sum();
+
+    let integral = times.fold(lbda0 * tmax, |acc, tj| {
+        acc + kernel.integral(tmax - tj)
+    });
+
+    evt_llhood - integral
+}
+
+/// Log-likelihood of the event times of a catalog under the ETAS model,
+/// given the magnitudes $m_i$ of the events:
+/// $$
+///     \ell =
+///     \sum_{i=1}^N \log\left(
+///         \mu + \sum_{j < i} e^{a(m_j - m_0)} g(t_i - t_j)
+///     \right)
+///     - \mu T
+///     - \sum_{i=1}^N e^{a(m_i - m_0)} \int_0^{T - t_i} g(s) ds
+/// $$
+/// with $g$ the Omori-Utsu kernel. The log-likelihood of the magnitudes
+/// is given separately by [`Etas::magnitude_log_likelihood`].
+/// Returns an error if there are not as many magnitudes as events.
+pub fn etas_likelihood(
+    times: ArrayView1<f64>,
+    magnitudes: ArrayView1<f64>,
+    model: &Etas,
+    tmax: f64) -> Result<f64, PointProcessError>
+{
+    check_catalog(times, magnitudes)?;
+    let mu = model.get_background().intensity(0.);
+    let kernel = model.get_kernel();
+
+    let evt_llhood: f64 = (0..times.len())
+        .into_par_iter()
+        .map(|i| {
+            let ti = times[i];
+            let lbda = (0..i).fold(mu, |acc, j| {
+                acc + model.triggering(ti - times[j], magnitudes[j])
+            });
+            lbda.ln()
+        }).sum();
+
+    let integral = times.iter()
+        .zip(magnitudes.iter())
+        .fold(mu * tmax, |acc, (tj, mj)| {
+            acc + model.productivity(*mj) * kernel.integral(tmax - tj)
+        });
+
+    Ok(evt_llhood - integral)
+}
+
+impl Likelihood for PowerLawHawkes {
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64 {
+        kernel_hawkes_likelihood(times, self, tmax)
+    }
+}
+
+impl Likelihood for TabulatedHawkes {
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64 {
+        kernel_hawkes_likelihood(times, self, tmax)
+    }
+}
+
+impl Likelihood for Hawkes<ConstBackground, PiecewiseConstKernel> {
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64 {
+        kernel_hawkes_likelihood(times, self, tmax)
+    }
+}
+
+impl Likelihood for SelfCorrecting {
+    /// With $t_0 = 0$ and $t_{N+1} = T$,
+    /// $$
+    ///     \ell = \sum_{i=1}^N (\mu + \nu t_i - \rho(i-1))
+    ///     - \sum_{k=0}^N \int_{t_k}^{t_{k+1}} e^{\mu + \nu t - \rho k} dt
+    /// $$
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64 {
+        let (mu, nu, rho) = (self.get_mu(), self.get_nu(), self.get_rho());
+        let evt_llhood = times.iter().enumerate().fold(0., |acc, (i, t)| {
+            acc + mu + nu * t - rho * i as f64
+        });
+
+        let mut last = 0.;
+        let integral = times.iter()
+            .chain(std::iter::once(&tmax))
+            .enumerate()
+            .fold(0., |acc, (k, t)| {
+                let res = acc + self.integrated_intensity(last, *t, k);
+                last = *t;
+                res
+            });
+
+        evt_llhood - integral
+    }
+
+    /// Gradient with respect to $(\mu, \nu, \rho)$.
+    fn grad(&self, times: ArrayView1<f64>, tmax: f64) -> Option<Array1<f64>> {
+        let nu = self.get_nu();
+        let n_events = times.len() as f64;
+
+        // derivatives of the event terms
+        let mut d_mu = n_events;
+        let mut d_nu = times.sum();
+        let mut d_rho = -0.5 * n_events * (n_events - 1.);
+
+        let mut last = 0.;
+        for (k, t) in times.iter().chain(std::iter::once(&tmax)).enumerate() {
+            let h = t - last;
+            let integral = self.integrated_intensity(last, *t, k);
+            // integral of (s - last) * exp(nu * (s - last)) over [0, h]
+            let x = nu * h;
+            let moment = if x.abs() < 1e-4 {
+                0.5 * h * h * (1. + 2. * x / 3. + 0.25 * x * x)
+            } else {
+                (h * x.exp() - x.exp_m1() / nu) / nu
+            };
+            d_mu -= integral;
+            d_nu -= last * integral + self.conditional_intensity(last, k) * moment;
+            d_rho += k as f64 * integral;
+            last = *t;
+        }
+
+        Some(arr1(&[d_mu, d_nu, d_rho]))
+    }
+}
@@ -5,11 +5,119 @@
 //! $$
 mod hawkes;
 
-pub use hawkes::{hawkes_likelihood,HawkesLikelihood};
+pub use hawkes::{hawkes_likelihood,sumexp_hawkes_likelihood,kernel_hawkes_likelihood,etas_likelihood,HawkesLikelihood};
 
 use ndarray::prelude::*;
+use rayon::prelude::*;
 
-use crate::temporal::{PoissonProcess, DeterministicIntensity};
+use crate::temporal::{PoissonProcess, VariablePoissonProcess, DeterministicIntensity};
+use crate::temporal::renewal::{RenewalProcess, InterArrival};
+use crate::temporal::utils::{simpson, QUADRATURE_STEPS};
+
+
+/// Models with a computable log-likelihood, for events observed on $[0, T]$.
+pub trait Likelihood {
+    /// Log-likelihood of the events `times` observed on $[0, T]$.
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64;
+
+    /// Gradient of the log-likelihood with respect to the model parameters,
+    /// if it is available.
+    fn grad(&self, _times: ArrayView1<f64>, _tmax: f64) -> Option<Array1<f64>> {
+        None
+    }
+
+    /// Pooled log-likelihood of independent sequences of events, each
+    /// given with the end $T$ of its observation window.
+    /// The sequences are evaluated in parallel.
+    fn pooled_log_likelihood(&self, sequences: &[(ArrayView1<f64>, f64)]) -> f64
+    where Self: Sync
+    {
+        sequences.par_iter()
+            .map(|(times, tmax)| self.log_likelihood(*times, *tmax))
+            .sum()
+    }
+
+    /// Gradient of the pooled log-likelihood, if it is available.
+    /// Returns `None` if there are no sequences.
+    fn pooled_grad(&self, sequences: &[(ArrayView1<f64>, f64)]) -> Option<Array1<f64>>
+    where Self: Sync
+    {
+        let grads: Option<Vec<Array1<f64>>> = sequences.par_iter()
+            .map(|(times, tmax)| self.grad(*times, *tmax))
+            .collect();
+        grads?.into_iter().fold(None, |acc, g| match acc {
+            Some(total) => Some(total + &g),
+            None => Some(g)
+        })
+    }
+}
+
+impl Likelihood for PoissonProcess {
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64 {
+        poisson_likelihood(times, self, tmax)
+    }
+
+    /// Gradient with respect to $\lambda$.
+    fn grad(&self, times: ArrayView1<f64>, tmax: f64) -> Option<Array1<f64>> {
+        let lbda = self.intensity(0.);
+        Some(arr1(&[times.len() as f64 / lbda - tmax]))
+    }
+}
+
+impl<F> Likelihood for VariablePoissonProcess<F>
+where F: Fn(f64) -> f64 + Send + Sync
+{
+    /// The integral of the intensity is computed with the composite Simpson rule
+    /// between consecutive events.
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64 {
+        let evt_llhood = times.fold(0., |acc, t| {
+            acc + self.intensity(*t).ln()
+        });
+
+        let mut last = 0.;
+        let integral = times.iter()
+            .chain(std::iter::once(&tmax))
+            .fold(0., |acc, t| {
+                let res = acc + simpson(|s| self.intensity(s), last, *t, QUADRATURE_STEPS);
+                last = *t;
+                res
+            });
+
+        evt_llhood - integral
+    }
+}
+
+impl<D: InterArrival> Likelihood for RenewalProcess<D> {
+    /// Renewal likelihood, with $\tau_i = t_i - t_{i-1}$ and $t_0 = 0$:
+    /// $$
+    ///     \ell = \sum_i \ln f(\tau_i) + \ln S(T - t_N)
+    /// $$
+    /// With a stationary start, the first inter-arrival time has density
+    /// $S(t)/\mu$ and survival function $S_e$ instead.
+    fn log_likelihood(&self, times: ArrayView1<f64>, tmax: f64) -> f64 {
+        let dist = self.get_dist();
+        let stationary = self.is_stationary();
+
+        let mut last = 0.;
+        let evt_llhood = times.iter().enumerate().fold(0., |acc, (i, t)| {
+            let tau = t - last;
+            last = *t;
+            if i == 0 && stationary {
+                acc + (dist.survival(tau) / dist.mean()).ln()
+            } else {
+                acc + dist.pdf(tau).ln()
+            }
+        });
+
+        let tail = if times.is_empty() && stationary {
+            dist.equilibrium_survival(tmax)
+        } else {
+            dist.survival(tmax - last)
+        };
+
+        evt_llhood + tail.ln()
+    }
+}
 
 /// Log-likelihood of the data under the given Poisson model
 /// $$ \ell(\lambda) =
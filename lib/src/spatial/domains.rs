@@ -3,6 +3,8 @@
  */
 use ndarray::prelude::*;
 
+use crate::error::PointProcessError;
+
 
 pub struct Domain {
     pub close: Array1<f64>,
@@ -13,4 +15,34 @@ impl Domain {
     pub fn new(close: Array1<f64>, far: Array1<f64>) -> Domain {
         Domain { close, far }
     }
+
+    /// Create a new domain, checking that the corners have the same, nonzero dimension
+    /// and that `close` is below `far` along every axis.
+    pub fn try_new(close: Array1<f64>, far: Array1<f64>) -> Result<Domain, PointProcessError> {
+        let domain = Domain { close, far };
+        domain.validate()?;
+        Ok(domain)
+    }
+
+    /// Check that the domain is a valid, nonempty box.
+    pub(crate) fn validate(&self) -> Result<(), PointProcessError> {
+        if self.close.len() != self.far.len() {
+            return Err(PointProcessError::InvalidParameter(
+                format!("domain corners have dimensions {} and {}",
+                        self.close.len(), self.far.len())));
+        }
+        if self.close.is_empty() {
+            return Err(PointProcessError::InvalidParameter(
+                "domain must have at least one dimension".to_string()));
+        }
+        let valid = self.close.iter()
+            .zip(self.far.iter())
+            .all(|(c, f)| c.is_finite() && f.is_finite() && c < f);
+        if valid {
+            Ok(())
+        } else {
+            Err(PointProcessError::InvalidParameter(
+                format!("domain must satisfy close < far, got {} and {}", self.close, self.far)))
+        }
+    }
 }
@@ -1,18 +1,44 @@
 use super::domains::Domain;
 
 use rand::prelude::*;
-use rand::rngs::SmallRng;
-use rand_distr::{Poisson, Distribution};
 
-use ndarray::stack;
 use ndarray::prelude::*;
 
-static XORSHIFT_ERR: &str = "Unable to create XorShift rng from thread local rng";
+use crate::error::{PointProcessError, check_nonnegative};
+use crate::temporal::utils::sample_poisson_count;
+
+static SPATIAL_ERR: &str = "Invalid intensity or domain";
+
 
 /// A higher-dimensional homogeneous Poisson process, for parallepipedal domains.
 pub fn poisson_process(lambda: f64, domain: &Domain) -> Array2<f64>
 {
-    let ref mut rng = thread_rng();
+    poisson_process_with_rng(lambda, domain, &mut thread_rng())
+}
+
+/// A higher-dimensional homogeneous Poisson process, for parallepipedal domains,
+/// using the supplied random number generator.
+pub fn poisson_process_with_rng<R>(lambda: f64, domain: &Domain, rng: &mut R) -> Array2<f64>
+where R: Rng + ?Sized
+{
+    try_poisson_process_with_rng(lambda, domain, rng).expect(SPATIAL_ERR)
+}
+
+/// Same as [`poisson_process`], returning an error if the intensity
+/// or the domain are invalid.
+pub fn try_poisson_process(lambda: f64, domain: &Domain) -> Result<Array2<f64>, PointProcessError>
+{
+    try_poisson_process_with_rng(lambda, domain, &mut thread_rng())
+}
+
+/// Same as [`poisson_process_with_rng`], returning an error if the intensity
+/// or the domain are invalid.
+pub fn try_poisson_process_with_rng<R>(
+    lambda: f64, domain: &Domain, rng: &mut R) -> Result<Array2<f64>, PointProcessError>
+where R: Rng + ?Sized
+{
+    check_nonnegative("lambda", lambda)?;
+    domain.validate()?;
     let far = &domain.far;
     let close = &domain.close;
 
@@ -24,34 +50,53 @@ pub fn poisson_process(lambda: f64, domain: &Domain) -> Array2<f64>
 
     // get number of events to generate
     // events outside of the set will be rejected
-    let fish = Poisson::new(lambda*area).unwrap();
-    let num_events: u64 = fish.sample(rng);
-    
-    let mut srng = SmallRng::from_rng(rng).expect(XORSHIFT_ERR);
-
-    let events: Vec<Array2<f64>> = (0..num_events).map(|_| {
-        // generate a point inside the bounding box
-        let mut ev: Array1<f64> = Array::zeros((d,));
+    let num_events = sample_poisson_count(lambda * area, rng)?;
 
+    // fill a single buffer with the coordinates of the points,
+    // generated inside the bounding box
+    let num_events = num_events as usize;
+    let mut coords: Vec<f64> = Vec::with_capacity(num_events * d);
+    for _ in 0..num_events {
         for i in 0..d {
-            ev[i] = srng.gen_range(close[i], far[i]);
+            coords.push(rng.gen_range(close[i], far[i]));
         }
+    }
 
-        // if it's in, then keep it
-        ev.into_shape((1,d)).unwrap()
-    }).collect();
-
-    let events_ref: Vec<ArrayView2<f64>> = events.iter().map(|ev| {
-        ev.view()
-    }).collect();
-
-    stack(Axis(0), events_ref.as_slice()).unwrap()
+    Ok(Array2::from_shape_vec((num_events, d), coords)?)
 }
 
 /// Poisson process on a d-dimensional region with variable intensity, using a rejection sampling algorithm.
 pub fn variable_poisson<F>(lambda: F, max_lambda: f64, domain: &Domain) -> Array2<f64>
     where F: Fn(&Array1<f64>) -> f64 + Sync + Send
 {
+    variable_poisson_with_rng(lambda, max_lambda, domain, &mut thread_rng())
+}
+
+/// Poisson process on a d-dimensional region with variable intensity,
+/// using the supplied random number generator.
+pub fn variable_poisson_with_rng<F, R>(lambda: F, max_lambda: f64, domain: &Domain, rng: &mut R) -> Array2<f64>
+    where F: Fn(&Array1<f64>) -> f64 + Sync + Send, R: Rng + ?Sized
+{
+    try_variable_poisson_with_rng(lambda, max_lambda, domain, rng).expect(SPATIAL_ERR)
+}
+
+/// Same as [`variable_poisson`], returning an error if the intensity bound
+/// or the domain are invalid.
+pub fn try_variable_poisson<F>(
+    lambda: F, max_lambda: f64, domain: &Domain) -> Result<Array2<f64>, PointProcessError>
+    where F: Fn(&Array1<f64>) -> f64 + Sync + Send
+{
+    try_variable_poisson_with_rng(lambda, max_lambda, domain, &mut thread_rng())
+}
+
+/// Same as [`variable_poisson_with_rng`], returning an error if the intensity bound
+/// or the domain are invalid.
+pub fn try_variable_poisson_with_rng<F, R>(
+    lambda: F, max_lambda: f64, domain: &Domain, rng: &mut R) -> Result<Array2<f64>, PointProcessError>
+    where F: Fn(&Array1<f64>) -> f64 + Sync + Send, R: Rng + ?Sized
+{
+    check_nonnegative("max_lambda", max_lambda)?;
+    domain.validate()?;
     let close = &domain.close;
     let far = &domain.far;
 
@@ -62,32 +107,25 @@ pub fn variable_poisson<F>(lambda: F, max_lambda: f64, domain: &Domain) -> Array
 
     // get number of events to generate
     // events outside of the set will be rejected
-    let ref mut rng = thread_rng();
-    let fish = Poisson::new(max_lambda*area).unwrap();
-    let num_events: u64 = fish.sample(rng);
-    
-    let mut srng = rand::rngs::SmallRng::from_rng(rng).expect(XORSHIFT_ERR);
+    let num_events = sample_poisson_count(max_lambda * area, rng)?;
 
-    let events: Vec<Array2<f64>> = (0..num_events).filter_map(|_| {
+    // fill a single buffer with the coordinates of the accepted points
+    let mut coords: Vec<f64> = Vec::with_capacity(num_events as usize * d);
+    let mut ev: Array1<f64> = Array::zeros((d,));
+    for _ in 0..num_events {
         // generate a point inside the bounding box
-        let mut ev: Array1<f64> = Array::zeros((d,));
-        let intens = max_lambda*random::<f64>();
+        let intens = max_lambda*rng.gen::<f64>();
 
         for i in 0..d {
-            ev[i] = srng.gen_range(close[i], far[i]);
+            ev[i] = rng.gen_range(close[i], far[i]);
         }
 
         // if it's in, then keep it
         if intens < lambda(&ev) {
-            Some(ev.into_shape((1,d)).unwrap())
-        } else {
-            None
+            coords.extend(ev.iter());
         }
-    }).collect();
-
-    let events_ref: Vec<ArrayView2<f64>> = events.iter().map(|ev| {
-        ev.view()
-    }).collect();
+    }
 
-    stack(Axis(0), &events_ref).unwrap()
+    let num_accepted = coords.len() / d;
+    Ok(Array2::from_shape_vec((num_accepted, d), coords)?)
 }
@@ -0,0 +1,104 @@
+//! Compound Poisson processes.
+//! The process is given by
+//! $$ S_t = \sum_{i=1}^{N_t} Y_i $$
+//! where $N_t$ is a Poisson process and the jumps $Y_i$ are i.i.d.
+use super::traits::*;
+use super::poisson::{PoissonProcess, VariablePoissonProcess};
+use super::marked::Marked;
+use rand::prelude::*;
+use rand_distr::Distribution;
+
+use ndarray::prelude::*;
+
+
+/// Result type for compound Poisson processes.
+#[derive(Debug)]
+pub struct CompoundPoissonResult {
+    /// Jump times.
+    pub timestamps: Array1<f64>,
+    /// Jump sizes.
+    pub jumps: Array1<f64>,
+    /// Grid the cumulative path is evaluated on.
+    pub times: Array1<f64>,
+    /// Cumulative path $S_t$ on the grid.
+    pub path: Array1<f64>
+}
+
+impl CompoundPoissonResult {
+    /// Evaluate the cumulative path $S_t$ at time `t`.
+    pub fn value_at(&self, t: f64) -> f64 {
+        self.timestamps.iter()
+            .zip(self.jumps.iter())
+            .take_while(|(ti, _)| **ti <= t)
+            .fold(0., |acc, (_, y)| acc + y)
+    }
+}
+
+/// Compound Poisson process, with jumps drawn from any distribution.
+/// The jump times are given by the underlying Poisson process,
+/// with either constant or variable intensity.
+#[derive(Debug)]
+pub struct CompoundPoisson<P, D> {
+    process: Marked<P, D>
+}
+
+/// Compound Poisson process with constant intensity.
+pub type ConstCompoundPoisson<D> = CompoundPoisson<PoissonProcess, D>;
+
+/// Compound Poisson process with variable intensity.
+pub type VariableCompoundPoisson<F, D> = CompoundPoisson<VariablePoissonProcess<F>, D>;
+
+impl<P, D> CompoundPoisson<P, D> {
+    /// Create a new compound Poisson process with jump times given by `process`
+    /// and jump sizes drawn from `jump_dist`.
+    pub fn new(process: P, jump_dist: D) -> Self {
+        CompoundPoisson {
+            process: Marked::new(process, jump_dist)
+        }
+    }
+
+    /// Get the underlying Poisson process.
+    pub fn get_process(&self) -> &P {
+        self.process.get_process()
+    }
+
+    /// Get the jump distribution.
+    pub fn get_jump_dist(&self) -> &D {
+        self.process.get_mark_dist()
+    }
+}
+
+impl<P, D> CompoundPoisson<P, D>
+where P: TemporalProcess, D: Distribution<f64>
+{
+    /// Sample the jumps of the process on $[0, T]$ and evaluate the
+    /// cumulative path on a regular grid of `num_points` points.
+    pub fn sample(&self, tmax: f64, num_points: usize) -> CompoundPoissonResult {
+        self.sample_with_rng(tmax, num_points, &mut thread_rng())
+    }
+
+    /// Sample the process using the supplied random number generator.
+    pub fn sample_with_rng(&self, tmax: f64, num_points: usize, rng: &mut dyn RngCore) -> CompoundPoissonResult
+    {
+        let events: MarkedProcessResult<f64> = self.process.sample_with_rng(tmax, rng);
+        let timestamps = events.timestamps;
+        let jumps = Array1::from_vec(events.marks);
+
+        let times = Array1::linspace(0., tmax, num_points);
+        let mut path = Array1::zeros(num_points);
+        // sweep the grid and the sorted jump times together
+        let mut k = 0;
+        let mut cum_sum = 0.;
+        for (i, t) in times.iter().enumerate() {
+            while k < timestamps.len() && timestamps[k] <= *t {
+                cum_sum += jumps[k];
+                k += 1;
+            }
+            path[i] = cum_sum;
+        }
+
+        CompoundPoissonResult {
+            timestamps, jumps, times, path
+        }
+    }
+}
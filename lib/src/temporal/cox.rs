@@ -2,38 +2,189 @@
  *
  */
 use crate::temporal::traits::*;
+use crate::temporal::utils::{simulate_brownian_with_rng, simulate_ornstein_uhlenbeck_with_rng};
+use crate::temporal::utils::sample_poisson_count;
 use rand::prelude::*;
-use rand_distr::{StandardNormal};
 
-use ndarray::array;
 use ndarray::prelude::*;
-use ndarray_parallel::prelude::*;
 
-use rayon::prelude::*;
+use crate::error::{PointProcessError, check_positive, check_nonnegative};
 
+static COX_PARAM_ERR: &str = "Invalid lognormal Cox process parameters";
 
-/// Lognormal Cox process.
+
+/// Latent Gaussian process $(X_t)$ driving the log-intensity of a Cox process.
+#[derive(Debug,Clone,Copy)]
+pub enum LatentProcess {
+    /// Standard Brownian motion $W_t$.
+    Brownian,
+    /// Ornstein-Uhlenbeck process $dX_t = -\theta X_t dt + dW_t$, with $X_0 = 0$.
+    OrnsteinUhlenbeck { theta: f64 }
+}
+
+/// Lognormal Cox process, with intensity
+/// $$ \lambda_t = \exp(\mu + \sigma X_t) $$
+/// where $(X_t)$ is a latent Gaussian process.
+/// The latent path is simulated on a regular grid of step `step`
+/// and the intensity is linearly interpolated between grid points.
+#[derive(Debug)]
 pub struct LognormalCox {
     mu: f64,
-    sigma: f64
+    sigma: f64,
+    latent: LatentProcess,
+    /// Time step of the latent process discretization.
+    step: f64
 }
 
+/// Result type for Cox processes: the events along with
+/// the latent intensity path they were sampled from.
+#[derive(Debug)]
+pub struct CoxProcessResult {
+    /// Event timestamps and intensities.
+    pub events: TimeProcessResult,
+    /// Grid the intensity path is evaluated on.
+    pub times: Array1<f64>,
+    /// Latent intensity path $\lambda_t$ on the grid.
+    pub intensity: Array1<f64>
+}
 
-impl StochasticIntensity for LognormalCox {}
+impl LognormalCox {
+    /// Create a new lognormal Cox process driven by a Brownian motion.
+    pub fn new(mu: f64, sigma: f64, step: f64) -> Self {
+        Self::try_new(mu, sigma, step).expect(COX_PARAM_ERR)
+    }
 
-impl TemporalProcess for LognormalCox {
-    /// Algorithm: acceptance-rejection method
-    /// Propose new event time, compute brownian increment,
-    /// update intensity and accept/reject
-    fn sample(&self, tmax: f64) -> TimeProcessResult {
-
-        let x = array![0.];
-        let y = array![0.];
-        TimeProcessResult {
-            timestamps: x,
-            intensities: y
+    /// Create a new lognormal Cox process driven by a Brownian motion,
+    /// checking that $\mu$ is finite, $\sigma$ nonnegative and finite and
+    /// the time step positive and finite.
+    pub fn try_new(mu: f64, sigma: f64, step: f64) -> Result<Self, PointProcessError> {
+        Self::check_params(mu, sigma, step)?;
+        Ok(LognormalCox {
+            mu, sigma,
+            latent: LatentProcess::Brownian,
+            step
+        })
+    }
+
+    /// Create a new lognormal Cox process driven by an Ornstein-Uhlenbeck process
+    /// with mean-reversion rate `theta`.
+    pub fn with_ornstein_uhlenbeck(mu: f64, sigma: f64, theta: f64, step: f64) -> Self {
+        Self::try_with_ornstein_uhlenbeck(mu, sigma, theta, step).expect(COX_PARAM_ERR)
+    }
+
+    /// Same as [`LognormalCox::with_ornstein_uhlenbeck`], returning an error if
+    /// the parameters are invalid or the mean-reversion rate is negative.
+    pub fn try_with_ornstein_uhlenbeck(mu: f64, sigma: f64, theta: f64, step: f64) -> Result<Self, PointProcessError> {
+        Self::check_params(mu, sigma, step)?;
+        check_nonnegative("theta", theta)?;
+        Ok(LognormalCox {
+            mu, sigma,
+            latent: LatentProcess::OrnsteinUhlenbeck { theta },
+            step
+        })
+    }
+
+    fn check_params(mu: f64, sigma: f64, step: f64) -> Result<(), PointProcessError> {
+        if !mu.is_finite() {
+            return Err(PointProcessError::InvalidParameter(
+                format!("mu must be finite, got {}", mu)));
+        }
+        check_nonnegative("sigma", sigma)?;
+        check_positive("step", step)
+    }
+
+    /// Get the latent process driving the intensity.
+    pub fn get_latent(&self) -> LatentProcess {
+        self.latent
+    }
+
+    /// Sample a sequence of events along with the latent intensity path.
+    ///
+    /// Algorithm: simulate the latent process on the grid, then
+    /// thin a homogeneous Poisson process with intensity $\max_k \lambda_{t_k}$.
+    pub fn sample_path(&self, tmax: f64) -> CoxProcessResult {
+        self.sample_path_with_rng(tmax, &mut thread_rng())
+    }
+
+    /// Sample a sequence of events along with the latent intensity path,
+    /// using the supplied random number generator.
+    /// An empty window yields no events and the intensity at time 0.
+    /// Panics if `tmax` is negative or not finite.
+    pub fn sample_path_with_rng<R>(&self, tmax: f64, rng: &mut R) -> CoxProcessResult
+    where R: Rng + ?Sized
+    {
+        check_nonnegative("tmax", tmax).unwrap_or_else(|err| panic!("{}", err));
+        if tmax == 0. {
+            // the latent process starts at 0
+            return CoxProcessResult {
+                events: TimeProcessResult {
+                    timestamps: Array1::zeros(0),
+                    intensities: Array1::zeros(0)
+                },
+                times: Array1::zeros(1),
+                intensity: Array1::from_elem(1, self.mu.exp())
+            };
+        }
+
+        let n = (tmax / self.step).ceil() as usize + 1;
+        let h = tmax / (n - 1) as f64;
+
+        let xt = match self.latent {
+            LatentProcess::Brownian => simulate_brownian_with_rng(h, n, rng),
+            LatentProcess::OrnsteinUhlenbeck { theta } => {
+                simulate_ornstein_uhlenbeck_with_rng(theta, h, n, rng)
+            }
+        };
+
+        let times = Array1::range(0., n as f64, 1.) * h;
+        let intensity: Array1<f64> = xt.iter()
+            .map(|x| (self.mu + self.sigma * x).exp())
+            .collect();
+
+        // piecewise linear intensity is bounded by its values on the grid
+        let max_lambda = intensity.fold(0., |acc: f64, l| acc.max(*l));
+        let interp = |t: f64| {
+            let k = ((t / h) as usize).min(n - 2);
+            let w = t / h - k as f64;
+            (1. - w) * intensity[k] + w * intensity[k+1]
+        };
+
+        let num_candidates = sample_poisson_count(tmax * max_lambda, rng)
+            .expect("Time window must be nonnegative and finite");
+
+        let mut candidates: Vec<f64> = (0..num_candidates)
+            .map(|_| rng.gen::<f64>() * tmax)
+            .collect();
+        candidates.sort_by(|a, b| a.total_cmp(b));
+
+        let mut timestamps = Vec::new();
+        let mut intensities = Vec::new();
+        for t in candidates {
+            let lambda_t = interp(t);
+            // acceptance-rejection step
+            if rng.gen::<f64>() * max_lambda < lambda_t {
+                timestamps.push(t);
+                intensities.push(lambda_t);
+            }
+        }
+
+        let events = TimeProcessResult {
+            timestamps: Array1::from_vec(timestamps),
+            intensities: Array1::from_vec(intensities)
+        };
+
+        CoxProcessResult {
+            events, times, intensity
         }
     }
 }
 
 
+impl StochasticIntensity for LognormalCox {}
+
+impl TemporalProcess for LognormalCox {
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult
+    {
+        self.sample_path_with_rng(tmax, rng).events
+    }
+}
@@ -0,0 +1,307 @@
+//! Epidemic-type aftershock sequence (ETAS) model.
+//! The ETAS model is a marked Hawkes process where each earthquake of
+//! magnitude $m_i$ triggers aftershocks with the Omori-Utsu kernel, scaled
+//! by a magnitude-dependent productivity:
+//! $$
+//!     \lambda_t = \mu + \sum_{t_i < t} e^{a(m_i - m_0)}\frac{K}{(c + t - t_i)^p}
+//! $$
+//! The magnitudes follow the Gutenberg-Richter law: $m_i - m_0$ is exponential
+//! with rate $\beta = b\ln 10$.
+use super::traits::*;
+use super::hawkes::{Kernel, PowerLawKernel, PowerLawHawkes, ConstBackground};
+use super::utils::sample_poisson_count;
+use rand::prelude::*;
+
+use ndarray::prelude::*;
+
+use crate::error::{PointProcessError, check_positive, check_nonnegative};
+
+static ETAS_COUNT_ERR: &str = "ETAS parameters must be nonnegative and finite";
+
+/// Check that a catalog has one magnitude per event.
+pub(crate) fn check_catalog(times: ArrayView1<f64>, magnitudes: ArrayView1<f64>) -> Result<(), PointProcessError> {
+    if times.len() != magnitudes.len() {
+        return Err(PointProcessError::InvalidData(
+            format!("got {} events but {} magnitudes", times.len(), magnitudes.len())));
+    }
+    Ok(())
+}
+
+
+/// ETAS model, built on a power-law Hawkes model for the background
+/// rate $\mu$ and the Omori-Utsu kernel $K/(c+t)^p$.
+#[derive(Debug)]
+pub struct Etas {
+    model: PowerLawHawkes,
+    /// Productivity exponent $a$.
+    productivity: f64,
+    /// Gutenberg-Richter $b$-value.
+    b_value: f64,
+    /// Reference (completeness) magnitude $m_0$.
+    ref_magnitude: f64
+}
+
+/// Catalog sampled from the ETAS model, with its branching structure.
+#[derive(Debug)]
+pub struct EtasCatalog {
+    pub timestamps: Array1<f64>,
+    pub intensities: Array1<f64>,
+    pub magnitudes: Array1<f64>,
+    /// Index of the event which triggered each event, or `None`
+    /// for background events.
+    pub parents: Vec<Option<usize>>
+}
+
+/// Result of the stochastic declustering of a catalog.
+#[derive(Debug)]
+pub struct Declustering {
+    /// Posterior probability that each event is a background event.
+    pub background_probs: Array1<f64>,
+    /// Parents drawn from the posterior branching probabilities, `None`
+    /// for the events drawn as background events.
+    pub parents: Vec<Option<usize>>
+}
+
+impl Etas {
+    /// Create a new ETAS model with background rate `mu`, Omori-Utsu
+    /// parameters `k`, `c` and `p`, productivity exponent `a`,
+    /// Gutenberg-Richter `b_value` and reference magnitude `ref_magnitude`.
+    pub fn new(mu: f64, k: f64, c: f64, p: f64, a: f64, b_value: f64, ref_magnitude: f64) -> Self {
+        Etas {
+            model: PowerLawHawkes::new(k, p, c, mu),
+            productivity: a,
+            b_value,
+            ref_magnitude
+        }
+    }
+
+    /// Create a new ETAS model, checking that the parameters are valid
+    /// and that the model is stationary, i.e. its branching ratio is below 1.
+    pub fn try_new(mu: f64, k: f64, c: f64, p: f64, a: f64, b_value: f64, ref_magnitude: f64) -> Result<Self, PointProcessError> {
+        check_positive("mu", mu)?;
+        check_nonnegative("k", k)?;
+        check_positive("c", c)?;
+        check_positive("p", p)?;
+        check_nonnegative("a", a)?;
+        check_positive("b_value", b_value)?;
+        if !ref_magnitude.is_finite() {
+            return Err(PointProcessError::InvalidParameter(
+                format!("ref_magnitude must be finite, got {}", ref_magnitude)));
+        }
+        let model = Self::new(mu, k, c, p, a, b_value, ref_magnitude);
+        let branching_ratio = model.branching_ratio();
+        if branching_ratio < 1. {
+            Ok(model)
+        } else {
+            Err(PointProcessError::NonStationary { branching_ratio })
+        }
+    }
+
+    /// Cap the size of the sampled catalogs, as for
+    /// [`Hawkes::with_max_events`](super::hawkes::Hawkes::with_max_events):
+    /// the aftershocks beyond the cap are dropped.
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.model = self.model.with_max_events(max_events);
+        self
+    }
+
+    /// Get the underlying power-law Hawkes model.
+    pub fn get_model(&self) -> &PowerLawHawkes {
+        &self.model
+    }
+
+    /// Get the Omori-Utsu kernel.
+    pub fn get_kernel(&self) -> &PowerLawKernel {
+        self.model.get_kernel()
+    }
+
+    /// Get the background intensity.
+    pub fn get_background(&self) -> &ConstBackground {
+        self.model.get_background()
+    }
+
+    /// Get the productivity exponent $a$.
+    pub fn get_productivity(&self) -> f64 {
+        self.productivity
+    }
+
+    /// Get the Gutenberg-Richter $b$-value.
+    pub fn get_b_value(&self) -> f64 {
+        self.b_value
+    }
+
+    /// Get the reference magnitude $m_0$.
+    pub fn get_ref_magnitude(&self) -> f64 {
+        self.ref_magnitude
+    }
+
+    /// Rate $\beta = b\ln 10$ of the exponential magnitude distribution.
+    fn magnitude_rate(&self) -> f64 {
+        self.b_value * std::f64::consts::LN_10
+    }
+
+    /// Productivity $e^{a(m - m_0)}$ of an event of magnitude `magnitude`.
+    pub fn productivity(&self, magnitude: f64) -> f64 {
+        (self.productivity * (magnitude - self.ref_magnitude)).exp()
+    }
+
+    /// Contribution $e^{a(m - m_0)}K/(c + t)^p$ to the intensity of an event
+    /// of magnitude `magnitude`, after a delay `dt`.
+    pub fn triggering(&self, dt: f64, magnitude: f64) -> f64 {
+        self.productivity(magnitude) * self.get_kernel().eval(dt)
+    }
+
+    /// Branching ratio: the mean number of aftershocks directly triggered
+    /// by an event, $\|g\|_1 \beta/(\beta - a)$, infinite if $a \geq \beta$.
+    pub fn branching_ratio(&self) -> f64 {
+        let beta = self.magnitude_rate();
+        if self.productivity >= beta {
+            return f64::INFINITY;
+        }
+        self.model.branching_ratio() * beta / (beta - self.productivity)
+    }
+
+    /// Draw a magnitude from the Gutenberg-Richter law.
+    fn sample_magnitude<R>(&self, rng: &mut R) -> f64
+    where R: Rng + ?Sized
+    {
+        self.ref_magnitude - rng.gen::<f64>().ln() / self.magnitude_rate()
+    }
+
+    /// Delay $s$ such that $\int_0^s K/(c+u)^p du$ is `mass`.
+    fn omori_inverse_integral(&self, mass: f64) -> f64 {
+        let kernel = self.get_kernel();
+        let (k, p, c) = (kernel.get_alpha(), kernel.get_beta(), kernel.get_delta());
+        if (p - 1.).abs() < 1e-12 {
+            c * (mass / k).exp_m1()
+        } else {
+            (c.powf(1. - p) - mass * (p - 1.) / k).powf(1. / (1. - p)) - c
+        }
+    }
+
+    /// Conditional intensity at each event of the catalog.
+    fn event_intensities(&self, times: ArrayView1<f64>, magnitudes: ArrayView1<f64>) -> Array1<f64> {
+        let mu = self.get_background().intensity(0.);
+        Array1::from_shape_fn(times.len(), |i| {
+            (0..i).fold(mu, |acc, j| {
+                acc + self.triggering(times[i] - times[j], magnitudes[j])
+            })
+        })
+    }
+
+    /// Sample a catalog on $[0, T]$ by simulating the cluster structure:
+    /// the background events are drawn from a Poisson process, then each event
+    /// triggers a Poisson number of direct aftershocks, with delays drawn
+    /// from the Omori-Utsu law truncated to the observation window.
+    pub fn sample_catalog_with_rng<R>(&self, tmax: f64, rng: &mut R) -> EtasCatalog
+    where R: Rng + ?Sized
+    {
+        let cap = self.model.event_cap();
+        let mu = self.get_background().intensity(0.);
+        let kernel = self.get_kernel();
+
+        // events as (time, magnitude, parent), in the order they are generated
+        let mut events: Vec<(f64, f64, Option<usize>)> = Vec::new();
+        let num_background = sample_poisson_count(mu * tmax, rng)
+            .expect(ETAS_COUNT_ERR) as usize;
+        for _ in 0..num_background.min(cap.remaining(0)) {
+            let t = rng.gen::<f64>() * tmax;
+            events.push((t, self.sample_magnitude(rng), None));
+        }
+
+        // each event is processed once, after its parent
+        let mut cur = 0;
+        while cur < events.len() && !cap.reached(events.len()) {
+            let (t, m, _) = events[cur];
+            let window_mass = kernel.integral(tmax - t);
+            let num_children = sample_poisson_count(self.productivity(m) * window_mass, rng)
+                .expect(ETAS_COUNT_ERR) as usize;
+            for _ in 0..num_children.min(cap.remaining(events.len())) {
+                let delay = self.omori_inverse_integral(rng.gen::<f64>() * window_mass);
+                let child_time = (t + delay).min(tmax);
+                events.push((child_time, self.sample_magnitude(rng), Some(cur)));
+            }
+            cur += 1;
+        }
+
+        // sort the catalog by time and relabel the parents
+        let mut order: Vec<usize> = (0..events.len()).collect();
+        order.sort_by(|&i, &j| events[i].0.total_cmp(&events[j].0));
+        let mut rank = vec![0; events.len()];
+        for (r, &i) in order.iter().enumerate() {
+            rank[i] = r;
+        }
+
+        let timestamps: Array1<f64> = order.iter().map(|&i| events[i].0).collect();
+        let magnitudes: Array1<f64> = order.iter().map(|&i| events[i].1).collect();
+        let parents = order.iter().map(|&i| events[i].2.map(|j| rank[j])).collect();
+        let intensities = self.event_intensities(timestamps.view(), magnitudes.view());
+
+        EtasCatalog {
+            timestamps, intensities, magnitudes, parents
+        }
+    }
+
+    /// Sample a catalog on $[0, T]$.
+    pub fn sample_catalog(&self, tmax: f64) -> EtasCatalog {
+        self.sample_catalog_with_rng(tmax, &mut thread_rng())
+    }
+
+    /// Log-likelihood of the Gutenberg-Richter law for the magnitudes,
+    /// which is separate from the likelihood of the event times.
+    pub fn magnitude_log_likelihood(&self, magnitudes: ArrayView1<f64>) -> f64 {
+        let beta = self.magnitude_rate();
+        magnitudes.fold(0., |acc, m| {
+            acc + beta.ln() - beta * (m - self.ref_magnitude)
+        })
+    }
+
+    /// Stochastic declustering of a catalog, sorted by time:
+    /// the posterior probability that event $i$ was triggered by event $j < i$ is
+    /// $e^{a(m_j - m_0)}g(t_i - t_j)/\lambda_{t_i}$, and that it is a background
+    /// event $\mu/\lambda_{t_i}$. A parent is drawn for every event
+    /// from these probabilities.
+    /// Returns an error if there are not as many magnitudes as events.
+    pub fn decluster_with_rng<R>(
+        &self,
+        times: ArrayView1<f64>,
+        magnitudes: ArrayView1<f64>,
+        rng: &mut R) -> Result<Declustering, PointProcessError>
+    where R: Rng + ?Sized
+    {
+        check_catalog(times, magnitudes)?;
+        let intensities = self.event_intensities(times, magnitudes);
+        let mu = self.get_background().intensity(0.);
+
+        let background_probs = intensities.mapv(|lbda| mu / lbda);
+        let parents = (0..times.len()).map(|i| {
+            // draw the parent, the background being last
+            let threshold = rng.gen::<f64>() * intensities[i];
+            let mut cumul = 0.;
+            (0..i).find(|&j| {
+                cumul += self.triggering(times[i] - times[j], magnitudes[j]);
+                threshold < cumul
+            })
+        }).collect();
+
+        Ok(Declustering { background_probs, parents })
+    }
+
+    /// Stochastic declustering of a catalog, sorted by time.
+    pub fn decluster(&self, times: ArrayView1<f64>, magnitudes: ArrayView1<f64>) -> Result<Declustering, PointProcessError> {
+        self.decluster_with_rng(times, magnitudes, &mut thread_rng())
+    }
+}
+
+impl MarkedTemporalProcess<f64> for Etas {
+    /// The marks are the magnitudes of the events.
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> MarkedProcessResult<f64>
+    {
+        let catalog = self.sample_catalog_with_rng(tmax, rng);
+        MarkedProcessResult {
+            timestamps: catalog.timestamps,
+            intensities: catalog.intensities,
+            marks: catalog.magnitudes.to_vec()
+        }
+    }
+}
@@ -4,12 +4,59 @@ use rand::prelude::*;
 
 use ndarray::prelude::*;
 
+use super::utils::{PiecewiseTable, EventCap};
+pub use super::utils::Interpolation;
 use crate::poisson::{PoissonProcess, VariablePoissonProcess};
+use crate::error::{PointProcessError, check_positive, check_nonnegative};
+
+static KERNEL_BOUND_ERR: &str = "Kernel must be monotone or report an upper bound to be simulated by thinning";
+/// Number of grid steps used to solve for the expected event count.
+static EXPECTED_COUNT_STEPS: usize = 1000;
 
 
 /// Kernel $g$ for the Hawkes process.
 pub trait Kernel {
     fn eval(&self, t: f64) -> f64;
+
+    /// Integral $\int_0^t g(s) ds$ of the kernel.
+    fn integral(&self, t: f64) -> f64;
+
+    /// Total mass $\|g\|_1 = \int_0^\infty g(s) ds$ of the kernel,
+    /// which is the branching ratio of the Hawkes process.
+    fn l1_norm(&self) -> f64 {
+        self.integral(f64::INFINITY)
+    }
+
+    /// End of the support of the kernel, beyond which it vanishes.
+    fn support(&self) -> f64 {
+        f64::INFINITY
+    }
+
+    /// Whether the kernel is nonincreasing on $[0,\infty[$.
+    fn is_monotone(&self) -> bool {
+        false
+    }
+
+    /// Upper bound $\sup_{s\geq t} g(s)$ of the kernel on $[t,\infty[$, if known.
+    /// Nonincreasing kernels are bounded by $g(t)$.
+    /// A kernel which reports a bound at $t = 0$ must report one for every $t$.
+    fn upper_bound(&self, t: f64) -> Option<f64> {
+        if self.is_monotone() {
+            Some(self.eval(t))
+        } else {
+            None
+        }
+    }
+
+    /// Simulate a Hawkes process with this kernel.
+    /// Defaults to Ogata's thinning algorithm, which requires the kernel
+    /// to be monotone or to report an upper bound.
+    /// Kernels with a recursive structure can override this with a faster algorithm.
+    fn simulate<T, R>(model: &Hawkes<T, Self>, tmax: f64, rng: &mut R) -> TimeProcessResult
+    where T: BoundedIntensity, R: Rng + ?Sized, Self: Sized
+    {
+        simulate_hawkes_ogata(model, tmax, rng)
+    }
 }
 
 /// The Hawkes process is a self-exciting point process:
@@ -19,10 +66,101 @@ pub trait Kernel {
 #[derive(Debug)]
 pub struct Hawkes<T, K: Kernel> {
     background: T,
-    kernel: K
+    kernel: K,
+    /// Maximum number of events to simulate.
+    max_events: EventCap
 }
 
 impl<T, K: Kernel> Hawkes<T, K> {
+    /// Create a Hawkes model from any background intensity and kernel.
+    pub fn with_kernel(background: T, kernel: K) -> Self {
+        Self {
+            background, kernel, max_events: EventCap::default()
+        }
+    }
+
+    /// Stop the simulations once `max_events` events have been sampled.
+    ///
+    /// A model whose branching ratio is at least 1 is explosive: its number
+    /// of events grows without bound, and the samplers would not terminate
+    /// in reasonable time or memory. With a cap, the sampled trajectories end
+    /// at the last accepted event, before the end of the time window, and
+    /// do not follow the distribution of the process on the whole window.
+    /// The cap applies to every sampler of the model.
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.max_events = EventCap(Some(max_events));
+        self
+    }
+
+    /// Get the maximum number of simulated events, if any.
+    pub fn get_max_events(&self) -> Option<usize> {
+        self.max_events.0
+    }
+
+    /// Cap on the number of simulated events.
+    pub(crate) fn event_cap(&self) -> EventCap {
+        self.max_events
+    }
+
+    /// Branching ratio $\|g\|_1$ of the process: the mean number of
+    /// events directly triggered by each event.
+    /// The process is stationary if it is below 1.
+    pub fn branching_ratio(&self) -> f64 {
+        self.kernel.l1_norm()
+    }
+
+    /// Check that the kernel can be simulated by Ogata's thinning algorithm,
+    /// i.e. that it reports an upper bound.
+    fn check_simulable(&self) -> Result<(), PointProcessError> {
+        match self.kernel.upper_bound(0.) {
+            Some(_) => Ok(()),
+            None => Err(PointProcessError::InvalidParameter(KERNEL_BOUND_ERR.to_string()))
+        }
+    }
+
+    /// Return the model if it is stationary.
+    fn check_stationary(self) -> Result<Self, PointProcessError> {
+        let branching_ratio = self.branching_ratio();
+        if branching_ratio < 1. {
+            Ok(self)
+        } else {
+            Err(PointProcessError::NonStationary { branching_ratio })
+        }
+    }
+
+    /// Expected number of events $\mathbb E\[N_T\]$ on $[0, T]$,
+    /// starting without any past events.
+    /// The cumulative mean $M(t) = \mathbb E\[N_t\]$ solves the renewal equation
+    /// $$
+    ///     M(t) = \int_0^t \lambda_0(s) ds + \int_0^t G(t - s) dM(s)
+    /// $$
+    /// where $G(t) = \int_0^t g(s) ds$, which is discretized on a regular grid.
+    pub fn expected_count(&self, tmax: f64) -> f64
+    where T: DeterministicIntensity
+    {
+        let num_steps = EXPECTED_COUNT_STEPS;
+        let h = tmax / num_steps as f64;
+        // kernel integral at the grid midpoints
+        let kernel_integrals: Vec<f64> = (0..num_steps)
+            .map(|m| self.kernel.integral((m as f64 + 0.5) * h))
+            .collect();
+
+        let mut background_count = 0.;
+        let mut increments: Vec<f64> = Vec::with_capacity(num_steps);
+        let mut count = 0.;
+        for k in 1..=num_steps {
+            background_count += h * self.background.intensity((k as f64 - 0.5) * h);
+            let triggered = increments.iter()
+                .enumerate()
+                .fold(0., |acc, (j, dm)| acc + kernel_integrals[k - 1 - j] * dm);
+            let new_count = (background_count + triggered - kernel_integrals[0] * count)
+                / (1. - kernel_integrals[0]);
+            increments.push(new_count - count);
+            count = new_count;
+        }
+        count
+    }
+
     /// Get Hawkes kernel object.
     pub fn get_kernel(&self) -> &K {
         &self.kernel
@@ -34,6 +172,19 @@ impl<T, K: Kernel> Hawkes<T, K> {
     }
 }
 
+impl<K: Kernel> Hawkes<ConstBackground, K> {
+    /// Mean intensity $\lambda_0/(1 - \|g\|_1)$ of the stationary process.
+    /// Returns an error if the model is not stationary.
+    pub fn stationary_intensity(&self) -> Result<f64, PointProcessError> {
+        let branching_ratio = self.branching_ratio();
+        if branching_ratio < 1. {
+            Ok(self.background.intensity(0.) / (1. - branching_ratio))
+        } else {
+            Err(PointProcessError::NonStationary { branching_ratio })
+        }
+    }
+}
+
 
 // BACKGROUND INTENSITIES
 
@@ -57,6 +208,21 @@ impl Kernel for ExpKernel {
     fn eval(&self, t: f64) -> f64 {
         self.alpha * (-self.beta * t).exp()
     }
+
+    fn integral(&self, t: f64) -> f64 {
+        self.alpha / self.beta * (1. - (-self.beta * t).exp())
+    }
+
+    fn is_monotone(&self) -> bool {
+        true
+    }
+
+    /// Uses the linear-time recursive algorithm.
+    fn simulate<T, R>(model: &Hawkes<T, Self>, tmax: f64, rng: &mut R) -> TimeProcessResult
+    where T: BoundedIntensity, R: Rng + ?Sized
+    {
+        simulate_hawkes_exp(model, tmax, rng)
+    }
 }
 
 // SUM OF EXPONENTIALS KERNEL
@@ -82,6 +248,37 @@ impl SumExpKernel {
             betas
         }
     }
+
+    /// Create a new SumExpKernel, checking that there are as many
+    /// jump sizes as decay rates, and that they are valid.
+    pub fn try_new(alphas: Vec<f64>, betas: Vec<f64>) -> Result<Self, PointProcessError> {
+        if alphas.len() != betas.len() {
+            return Err(PointProcessError::InvalidParameter(
+                format!("got {} jump sizes for {} decay rates", alphas.len(), betas.len())));
+        }
+        for alpha in alphas.iter() {
+            check_nonnegative("alpha", *alpha)?;
+        }
+        for beta in betas.iter() {
+            check_positive("beta", *beta)?;
+        }
+        Ok(Self::new(alphas, betas))
+    }
+
+    /// Number of exponentials in the kernel.
+    pub fn get_num_exp(&self) -> usize {
+        self.num_exp
+    }
+
+    /// Get the jump sizes $\alpha_j$.
+    pub fn get_alphas(&self) -> &[f64] {
+        &self.alphas
+    }
+
+    /// Get the decay rates $\beta_j$.
+    pub fn get_betas(&self) -> &[f64] {
+        &self.betas
+    }
 }
 
 impl Kernel for SumExpKernel {
@@ -93,6 +290,199 @@ impl Kernel for SumExpKernel {
         }
         res
     }
+
+    fn integral(&self, t: f64) -> f64 {
+        self.alphas.iter().zip(self.betas.iter())
+            .fold(0., |acc, (alpha, beta)| {
+                acc + alpha / beta * (1. - (-beta * t).exp())
+            })
+    }
+
+    fn is_monotone(&self) -> bool {
+        true
+    }
+
+    /// Uses the linear-time recursive algorithm, with one state per exponential.
+    fn simulate<T, R>(model: &Hawkes<T, Self>, tmax: f64, rng: &mut R) -> TimeProcessResult
+    where T: BoundedIntensity, R: Rng + ?Sized
+    {
+        simulate_hawkes_sumexp(model, tmax, rng)
+    }
+}
+
+/// Hawkes model with a sum-of-exponentials kernel and constant background intensity.
+pub type SumExpHawkes = Hawkes<ConstBackground, SumExpKernel>;
+
+impl SumExpHawkes {
+    /// Create a new sum-of-exponentials Hawkes model instance.
+    pub fn new(alphas: Vec<f64>, betas: Vec<f64>, lambda0: f64) -> Self {
+        let kernel = SumExpKernel::new(alphas, betas);
+        let background = ConstBackground::new(lambda0);
+
+        Self::with_kernel(background, kernel)
+    }
+
+    /// Create a new sum-of-exponentials Hawkes model, checking that the
+    /// parameters are valid and that the model is stationary,
+    /// i.e. $\sum_j \alpha_j/\beta_j < 1$.
+    pub fn try_new(alphas: Vec<f64>, betas: Vec<f64>, lambda0: f64) -> Result<Self, PointProcessError> {
+        let kernel = SumExpKernel::try_new(alphas, betas)?;
+        check_positive("lambda0", lambda0)?;
+        Self::with_kernel(ConstBackground::new(lambda0), kernel).check_stationary()
+    }
+}
+
+
+// PIECEWISE CONSTANT KERNEL
+
+/// Piecewise-constant kernel with finite support, of the form
+/// $$ g(t) = h_k \quad\text{for } b_k \leq t < b_{k+1} $$
+/// and $g(t) = 0$ outside of $[b_0, b_K[$.
+#[derive(Debug,Clone)]
+pub struct PiecewiseConstKernel {
+    /// Table of the values $h_k$ at the bin edges $b_0 < \cdots < b_K$,
+    /// with a zero value at the last edge.
+    table: PiecewiseTable
+}
+
+impl PiecewiseConstKernel {
+    /// Create a new PiecewiseConstKernel from the bin edges and bin values.
+    pub fn new(edges: Vec<f64>, values: Vec<f64>) -> Self {
+        Self::try_new(edges, values)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new PiecewiseConstKernel, checking that there is at least
+    /// one bin and one more bin edge than bin values, that the edges are finite
+    /// and increasing, and that the values are finite.
+    pub fn try_new(edges: Vec<f64>, mut values: Vec<f64>) -> Result<Self, PointProcessError> {
+        if edges.len() != values.len() + 1 {
+            return Err(PointProcessError::InvalidParameter(
+                format!("got {} bin edges for {} bin values", edges.len(), values.len())));
+        }
+        if values.is_empty() {
+            return Err(PointProcessError::InvalidParameter(
+                "a piecewise-constant kernel needs at least one bin".to_string()));
+        }
+        values.push(0.);
+        let table = PiecewiseTable::try_new(edges, values, Interpolation::Constant)?;
+        Ok(PiecewiseConstKernel { table })
+    }
+
+    /// Get the bin edges.
+    pub fn get_edges(&self) -> &[f64] {
+        &self.table.nodes
+    }
+
+    /// Get the values on each bin.
+    pub fn get_values(&self) -> &[f64] {
+        &self.table.values[..self.table.num_segments()]
+    }
+
+    /// Index of the bin containing `t`, if any.
+    pub fn bin_index(&self, t: f64) -> Option<usize> {
+        self.table.segment(t)
+    }
+}
+
+impl Kernel for PiecewiseConstKernel {
+    fn eval(&self, t: f64) -> f64 {
+        self.table.eval(t)
+    }
+
+    fn integral(&self, t: f64) -> f64 {
+        self.table.integral(0., t)
+    }
+
+    fn support(&self) -> f64 {
+        self.table.end()
+    }
+
+    fn upper_bound(&self, t: f64) -> Option<f64> {
+        Some(self.table.upper_bound(t))
+    }
+}
+
+
+// TABULATED KERNEL
+
+/// Kernel given by a table of values $g(t_k) = v_k$ at the nodes
+/// $t_0 < \cdots < t_m$, interpolated in between.
+/// The kernel vanishes outside of its support $[t_0, t_m[$.
+#[derive(Debug,Clone)]
+pub struct TabulatedKernel {
+    table: PiecewiseTable
+}
+
+impl TabulatedKernel {
+    /// Create a new TabulatedKernel from the nodes, values at the nodes
+    /// and interpolation scheme.
+    pub fn new(nodes: Vec<f64>, values: Vec<f64>, interpolation: Interpolation) -> Self {
+        Self::try_new(nodes, values, interpolation)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new TabulatedKernel, checking that there are as many values
+    /// as nodes, at least two nodes, that the nodes are finite and increasing,
+    /// and that the values are finite.
+    pub fn try_new(nodes: Vec<f64>, values: Vec<f64>, interpolation: Interpolation) -> Result<Self, PointProcessError> {
+        let table = PiecewiseTable::try_new(nodes, values, interpolation)?;
+        Ok(TabulatedKernel { table })
+    }
+
+    /// Get the nodes.
+    pub fn get_nodes(&self) -> &[f64] {
+        &self.table.nodes
+    }
+
+    /// Get the values at the nodes.
+    pub fn get_values(&self) -> &[f64] {
+        &self.table.values
+    }
+
+    /// Get the interpolation scheme.
+    pub fn get_interpolation(&self) -> Interpolation {
+        self.table.interpolation
+    }
+}
+
+impl From<PiecewiseConstKernel> for TabulatedKernel {
+    /// Tabulate a piecewise-constant kernel on its bin edges.
+    fn from(kernel: PiecewiseConstKernel) -> Self {
+        TabulatedKernel { table: kernel.table }
+    }
+}
+
+impl Kernel for TabulatedKernel {
+    fn eval(&self, t: f64) -> f64 {
+        self.table.eval(t)
+    }
+
+    /// Exact integral of the interpolated kernel.
+    fn integral(&self, t: f64) -> f64 {
+        self.table.integral(0., t)
+    }
+
+    /// End $t_m$ of the support of the kernel.
+    fn support(&self) -> f64 {
+        self.table.end()
+    }
+
+    /// The interpolated kernel reaches its maximum over a segment
+    /// at one of the segment's ends.
+    fn upper_bound(&self, t: f64) -> Option<f64> {
+        Some(self.table.upper_bound(t))
+    }
+}
+
+/// Hawkes model with a tabulated kernel and constant background intensity.
+pub type TabulatedHawkes = Hawkes<ConstBackground, TabulatedKernel>;
+
+impl TabulatedHawkes {
+    /// Create a new tabulated Hawkes model instance.
+    pub fn new(kernel: TabulatedKernel, lambda0: f64) -> Self {
+        Self::with_kernel(ConstBackground::new(lambda0), kernel)
+    }
 }
 
 
@@ -107,10 +497,46 @@ pub struct PowerLawKernel {
     delta: f64
 }
 
+impl PowerLawKernel {
+    /// Get the kernel scale $\alpha$.
+    pub fn get_alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Get the kernel exponent $\beta$.
+    pub fn get_beta(&self) -> f64 {
+        self.beta
+    }
+
+    /// Get the kernel offset $\delta$.
+    pub fn get_delta(&self) -> f64 {
+        self.delta
+    }
+}
+
 impl Kernel for PowerLawKernel {
     fn eval(&self, t: f64) -> f64 {
         self.alpha / (self.delta + t).powf(self.beta)
     }
+
+    /// $$
+    ///     \int_0^t g(s) ds =
+    ///     \alpha\frac{\delta^{1-\beta} - (\delta + t)^{1-\beta}}{\beta - 1}
+    /// $$
+    /// for $\beta \neq 1$, and $\alpha\log(1 + t/\delta)$ otherwise.
+    /// The kernel has infinite mass for $\beta \leq 1$.
+    fn integral(&self, t: f64) -> f64 {
+        let (alpha, beta, delta) = (self.alpha, self.beta, self.delta);
+        if (beta - 1.).abs() < 1e-12 {
+            alpha * (1. + t / delta).ln()
+        } else {
+            alpha * (delta.powf(1. - beta) - (delta + t).powf(1. - beta)) / (beta - 1.)
+        }
+    }
+
+    fn is_monotone(&self) -> bool {
+        true
+    }
 }
 
 /// Hawkes model with a power-law kernel and constant background intensity.
@@ -123,12 +549,22 @@ impl PowerLawHawkes {
         let kernel = PowerLawKernel {
             alpha, beta, delta
         };
-        Self {
-            background: ConstBackground::new(lambda0), kernel
-        }
+        Self::with_kernel(ConstBackground::new(lambda0), kernel)
+    }
+
+    /// Create a new power law Hawkes model, checking that the parameters
+    /// are valid and that the model is stationary, which requires $\beta > 1$
+    /// and $\alpha\delta^{1-\beta} < \beta - 1$.
+    pub fn try_new(alpha: f64, beta: f64, delta: f64, lambda0: f64) -> Result<Self, PointProcessError> {
+        check_nonnegative("alpha", alpha)?;
+        check_positive("beta", beta)?;
+        check_positive("delta", delta)?;
+        check_positive("lambda0", lambda0)?;
+        Self::new(alpha, beta, delta, lambda0).check_stationary()
     }
 }
 
+
 // EXPONENTIAL HAWKES
 
 /// Hawkes model with an exponential kernel.
@@ -141,15 +577,16 @@ impl ExpHawkes {
         };
         let background = ConstBackground::new(lambda0);
         
-        Self {
-            background, kernel
-        }
+        Self::with_kernel(background, kernel)
     }
-}
 
-impl TemporalProcess for ExpHawkes {
-    fn sample(&self, tmax: f64) -> TimeProcessResult {
-        simulate_hawkes_exp_const_bk(self, tmax)
+    /// Create a new exponential Hawkes model, checking that the parameters
+    /// are valid and that the model is stationary, i.e. $\alpha < \beta$.
+    pub fn try_new(alpha: f64, beta: f64, lambda0: f64) -> Result<Self, PointProcessError> {
+        check_nonnegative("alpha", alpha)?;
+        check_positive("beta", beta)?;
+        check_positive("lambda0", lambda0)?;
+        Self::new(alpha, beta, lambda0).check_stationary()
     }
 }
 
@@ -158,84 +595,161 @@ where F: Fn(f64) -> f64 + Send + Sync {
     pub fn new(alpha: f64, beta: f64, func: F, max_lbda0: f64) -> Self {
         let kernel = ExpKernel { alpha, beta };
         let background = DeterministicBackground::new(func, max_lbda0);
-        Self {
-            background, kernel
-        }
+        Self::with_kernel(background, kernel)
     }
 }
 
-impl<F> TemporalProcess for Hawkes<DeterministicBackground<F>, ExpKernel>
-where F: Fn(f64) -> f64 + Send + Sync {
-    fn sample(&self, tmax: f64) -> TimeProcessResult {
-        simulate_hawkes_exp_var_bk(self, tmax)
+/// Hawkes processes with a bounded deterministic background intensity
+/// are sampled using the algorithm provided by their kernel.
+/// The kernel must report an upper bound, which is checked before sampling.
+impl<T, K> TemporalProcess for Hawkes<T, K>
+where T: BoundedIntensity, K: Kernel
+{
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult
+    {
+        self.check_simulable().unwrap_or_else(|err| panic!("{}", err));
+        K::simulate(self, tmax, rng)
+    }
+
+    /// Also returns an error if the kernel does not report an upper bound.
+    fn try_sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> Result<TimeProcessResult, PointProcessError>
+    {
+        check_nonnegative("tmax", tmax)?;
+        self.check_simulable()?;
+        Ok(K::simulate(self, tmax, rng))
     }
 }
 
-// NUMERICAL ALGORITHM
+// SELF-CORRECTING PROCESS
 
-/// Simulate a trajectory of an exponential kernel Hawkes jump process,
-/// using Ogata's algorithm (1982).
-/// Variant for constant background intensity.
-fn simulate_hawkes_exp_const_bk(model: &ExpHawkes, tmax: f64) -> TimeProcessResult {
-    let kernel = &model.kernel;
-    let alpha = kernel.alpha;
-    let decay = kernel.beta;
-    let lambda0 = &model.background.intensity(0.);
-    
-    let mut rng = thread_rng(); // random no. generator
-    let mut timestamps = Vec::new();
-    let mut intensities = Vec::new();
-    // compute a first event time, occurring as a standard poisson process
-    // of intensity lambda0
-    let mut s = -1.0/lambda0*rng.gen::<f64>().ln();
-    let mut cur_lambda = lambda0 + alpha;
-    let mut lbda_max = cur_lambda;
+/// Self-correcting (stress-release) point process, with intensity
+/// $$ \lambda_t = \exp(\mu + \nu t - \rho N_{t^-}) $$
+/// The intensity grows steadily, and every event releases part of it.
+#[derive(Debug,Clone)]
+pub struct SelfCorrecting {
+    mu: f64,
+    nu: f64,
+    rho: f64
+}
 
-    while s < tmax {
-        let u: f64 = rng.gen();
-        // candidate time
-        let ds = -1.0/lbda_max*u.ln();
-        // compute process intensity at new time s + ds
-        // by decaying over the interval [s, s+ds]
-        cur_lambda = lambda0 + (cur_lambda-lambda0)*(-decay*ds).exp();
-        s += ds; // update s
-        if s > tmax {
-            // time window is over, finish simulation loop
-            break;
+impl SelfCorrecting {
+    /// Create a new self-correcting process with log-intensity offset $\mu$,
+    /// growth rate $\nu$ and release $\rho$ per event.
+    pub fn new(mu: f64, nu: f64, rho: f64) -> Self {
+        SelfCorrecting {
+            mu, nu, rho
         }
+    }
 
-        // rejection sampling step
-        let d: f64 = rng.gen();
-        if d < cur_lambda/lbda_max {
-            // accept the event
-            cur_lambda = cur_lambda + alpha; // boost the intensity with the jump
-            timestamps.push(s);
-            intensities.push(cur_lambda);
+    /// Create a new self-correcting process, checking that $\mu$ and $\nu$
+    /// are finite and $\rho$ nonnegative and finite.
+    pub fn try_new(mu: f64, nu: f64, rho: f64) -> Result<Self, PointProcessError> {
+        for (name, value) in [("mu", mu), ("nu", nu)] {
+            if !value.is_finite() {
+                return Err(PointProcessError::InvalidParameter(
+                    format!("{} must be finite, got {}", name, value)));
+            }
         }
-        // update the intensity upper bound
-        lbda_max = cur_lambda; 
+        check_nonnegative("rho", rho)?;
+        Ok(Self::new(mu, nu, rho))
     }
 
-    let timestamps = Array1::from_vec(timestamps);
-    let intensities = Array1::from_vec(intensities);
+    /// Get the log-intensity offset $\mu$.
+    pub fn get_mu(&self) -> f64 {
+        self.mu
+    }
 
-    TimeProcessResult {
-        timestamps, intensities
+    /// Get the growth rate $\nu$ of the log-intensity.
+    pub fn get_nu(&self) -> f64 {
+        self.nu
+    }
+
+    /// Get the release $\rho$ of the log-intensity at each event.
+    pub fn get_rho(&self) -> f64 {
+        self.rho
+    }
+
+    /// Intensity at time `t`, after `count` events.
+    pub fn conditional_intensity(&self, t: f64, count: usize) -> f64 {
+        (self.mu + self.nu * t - self.rho * count as f64).exp()
+    }
+
+    /// Integral of the intensity over $[a, b]$, with `count` events before $a$
+    /// and none in between:
+    /// $$ \int_a^b \lambda_t dt = \lambda_a \frac{e^{\nu(b-a)} - 1}{\nu} $$
+    pub fn integrated_intensity(&self, a: f64, b: f64, count: usize) -> f64 {
+        let h = b - a;
+        let growth = if self.nu == 0. {
+            h
+        } else {
+            (self.nu * h).exp_m1() / self.nu
+        };
+        self.conditional_intensity(a, count) * growth
+    }
+
+    /// Time elapsed after $a$ until the integral of the intensity reaches
+    /// `mass`, with `count` events before $a$. It is infinite if the intensity
+    /// decreases too fast for the integral to ever reach `mass`.
+    fn time_to_mass(&self, a: f64, mass: f64, count: usize) -> f64 {
+        let ratio = mass / self.conditional_intensity(a, count);
+        if self.nu == 0. {
+            return ratio;
+        }
+        let arg = self.nu * ratio;
+        if arg <= -1. {
+            f64::INFINITY
+        } else {
+            arg.ln_1p() / self.nu
+        }
     }
 }
 
-fn simulate_hawkes_exp_var_bk<F>(
-    model: &Hawkes<DeterministicBackground<F>, ExpKernel>, 
-    tmax: f64
-) -> TimeProcessResult where F: Fn(f64) -> f64 + Send + Sync
+impl TemporalProcess for SelfCorrecting {
+    /// Exact sampler: between events the intensity is log-linear, so the
+    /// integrated intensity is inverted in closed form for each
+    /// $\mathrm{Exp}(1)$ waiting time.
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult
+    {
+        let mut timestamps = Vec::new();
+        let mut intensities = Vec::new();
+
+        let mut t = 0.;
+        loop {
+            let mass = -rng.gen::<f64>().ln();
+            t += self.time_to_mass(t, mass, timestamps.len());
+            if t >= tmax {
+                break;
+            }
+            intensities.push(self.conditional_intensity(t, timestamps.len()));
+            timestamps.push(t);
+        }
+
+        TimeProcessResult {
+            timestamps: Array1::from_vec(timestamps),
+            intensities: Array1::from_vec(intensities)
+        }
+    }
+}
+
+
+// NUMERICAL ALGORITHM
+
+/// Simulate a trajectory of an exponential kernel Hawkes jump process,
+/// using Ogata's algorithm (1982).
+/// The self-exciting part of the intensity is updated recursively,
+/// which makes the algorithm linear-time.
+fn simulate_hawkes_exp<T, R>(
+    model: &Hawkes<T, ExpKernel>,
+    tmax: f64,
+    rng: &mut R
+) -> TimeProcessResult where T: BoundedIntensity, R: Rng + ?Sized
 {
     let kernel = &model.kernel;
     let alpha = kernel.alpha;
     let beta = kernel.beta;
     let lambda0 = &model.background;  // background intensity
-    let max_lbda0 = model.background.get_max_lambda();
+    let max_lbda0 = model.background.max_intensity();
 
-    let mut rng = thread_rng(); // random no. generator
     let mut timestamps = Vec::new();
     let mut intensities = Vec::new();
 
@@ -248,7 +762,7 @@ fn simulate_hawkes_exp_var_bk<F>(
     let mut s = 0.;
     let mut cur_slbda = 0.;  // current self-exciting intensity
 
-    while s < tmax {
+    while s < tmax && !model.max_events.reached(timestamps.len()) {
         let u: f64 = rng.gen();
         // candidate next event time
         let ds = -u.ln()/max_lbda;
@@ -286,3 +800,130 @@ fn simulate_hawkes_exp_var_bk<F>(
     }
 }
 
+/// Simulate a trajectory of a sum-of-exponentials kernel Hawkes process.
+/// Same as the exponential kernel algorithm, with one recursively updated
+/// self-exciting intensity per exponential.
+fn simulate_hawkes_sumexp<T, R>(
+    model: &Hawkes<T, SumExpKernel>,
+    tmax: f64,
+    rng: &mut R
+) -> TimeProcessResult where T: BoundedIntensity, R: Rng + ?Sized
+{
+    let kernel = &model.kernel;
+    let alphas = &kernel.alphas;
+    let betas = &kernel.betas;
+    let lambda0 = &model.background;  // background intensity
+    let max_lbda0 = model.background.max_intensity();
+
+    let mut timestamps = Vec::new();
+    let mut intensities = Vec::new();
+
+    let mut max_lbda = max_lbda0;
+    let mut s = 0.;
+    // self-exciting intensity of each exponential
+    let mut cur_slbdas = vec![0.; kernel.num_exp];
+
+    while s < tmax && !model.max_events.reached(timestamps.len()) {
+        let u: f64 = rng.gen();
+        // candidate next event time
+        let ds = -u.ln()/max_lbda;
+        s += ds;
+        if s > tmax {
+            // end sampling
+            break;
+        }
+
+        // background intensity
+        let cur_blbda = lambda0.intensity(s);
+        // decay the self-exciting parts
+        for (slbda, beta) in cur_slbdas.iter_mut().zip(betas.iter()) {
+            *slbda *= (-beta * ds).exp();
+        }
+        let mut cur_slbda: f64 = cur_slbdas.iter().sum();
+        let cur_lbda = cur_blbda + cur_slbda;  // total intensity
+
+        // rejection sampling step
+        let d: f64 = rng.gen();
+        if d * max_lbda < cur_lbda {
+            // accept the candidate event time,
+            // add the jumps to the self-exciting intensities
+            for (slbda, alpha) in cur_slbdas.iter_mut().zip(alphas.iter()) {
+                *slbda += alpha;
+            }
+            cur_slbda = cur_slbdas.iter().sum();
+            timestamps.push(s);
+            intensities.push(cur_blbda + cur_slbda);
+        }
+        max_lbda = max_lbda0 + cur_slbda;  // update max intensity
+    }
+
+    let timestamps = Array1::from_vec(timestamps);
+    let intensities = Array1::from_vec(intensities);
+
+    TimeProcessResult {
+        timestamps, intensities
+    }
+}
+
+/// Simulate a trajectory of a Hawkes process with a generic kernel,
+/// using Ogata's thinning algorithm.
+/// The intensity upper bound is recomputed at every candidate time from
+/// the kernel upper bounds $\sup_{s\geq t-t_i} g(s)$.
+/// The whole history is kept, hence the quadratic complexity.
+fn simulate_hawkes_ogata<T, K, R>(model: &Hawkes<T, K>, tmax: f64, rng: &mut R) -> TimeProcessResult
+where T: BoundedIntensity, K: Kernel, R: Rng + ?Sized
+{
+    let kernel = &model.kernel;
+    let background = &model.background;
+    let max_lbda0 = background.max_intensity();
+
+    let mut timestamps: Vec<f64> = Vec::new();
+    let mut intensities = Vec::new();
+
+    let support = kernel.support();
+    // first event whose excitation has not vanished yet
+    let mut first = 0;
+
+    let mut s = 0.;
+    // intensity upper bound, valid until the next candidate time
+    let mut lbda_max = max_lbda0;
+
+    while s < tmax && !model.max_events.reached(timestamps.len()) {
+        let u: f64 = rng.gen();
+        // candidate time
+        s += -u.ln()/lbda_max;
+        if s > tmax {
+            // time window is over, finish simulation loop
+            break;
+        }
+
+        while first < timestamps.len() && s - timestamps[first] >= support {
+            first += 1;
+        }
+
+        let mut cur_lambda = timestamps[first..].iter()
+            .fold(background.intensity(s), |acc, ti| {
+                acc + kernel.eval(s - ti)
+            });
+        // rejection sampling step
+        let d: f64 = rng.gen();
+        if d * lbda_max < cur_lambda {
+            // accept the event, the intensity jumps by g(0)
+            cur_lambda += kernel.eval(0.);
+            timestamps.push(s);
+            intensities.push(cur_lambda);
+        }
+        // update the intensity upper bound
+        lbda_max = timestamps[first..].iter()
+            .fold(max_lbda0, |acc, ti| {
+                acc + kernel.upper_bound(s - ti).expect(KERNEL_BOUND_ERR)
+            });
+    }
+
+    let timestamps = Array1::from_vec(timestamps);
+    let intensities = Array1::from_vec(intensities);
+
+    TimeProcessResult {
+        timestamps, intensities
+    }
+}
@@ -0,0 +1,171 @@
+//! Marked temporal point processes.
+//! Each event $t_i$ carries a mark $m_i$, such as a jump size or a category,
+//! drawn from a mark distribution.
+use super::traits::*;
+use super::poisson::{PoissonProcess, VariablePoissonProcess};
+use super::hawkes::{ConstBackground, ExpKernel, ExpHawkes};
+use rand::prelude::*;
+use rand_distr::Distribution;
+
+use ndarray::prelude::*;
+
+
+/// Point process with independent marks: the marks are drawn i.i.d.
+/// from the mark distribution and do not affect the dynamics of the
+/// ground process.
+#[derive(Debug)]
+pub struct Marked<P, D> {
+    /// Ground process.
+    process: P,
+    /// Mark distribution.
+    mark_dist: D
+}
+
+impl<P, D> Marked<P, D> {
+    /// Mark the events of a ground process with marks drawn from `mark_dist`.
+    pub fn new(process: P, mark_dist: D) -> Self {
+        Marked {
+            process, mark_dist
+        }
+    }
+
+    /// Get the ground process.
+    pub fn get_process(&self) -> &P {
+        &self.process
+    }
+
+    /// Get the mark distribution.
+    pub fn get_mark_dist(&self) -> &D {
+        &self.mark_dist
+    }
+}
+
+impl<P, D, M> MarkedTemporalProcess<M> for Marked<P, D>
+where P: TemporalProcess, D: Distribution<M>
+{
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> MarkedProcessResult<M>
+    {
+        let TimeProcessResult { timestamps, intensities } = self.process.sample_with_rng(tmax, rng);
+        let marks = (0..timestamps.len())
+            .map(|_| self.mark_dist.sample(rng))
+            .collect();
+        MarkedProcessResult {
+            timestamps, intensities, marks
+        }
+    }
+}
+
+/// Marked homogeneous Poisson process.
+pub type MarkedPoissonProcess<D> = Marked<PoissonProcess, D>;
+
+/// Marked Poisson process with variable intensity.
+pub type MarkedVariablePoissonProcess<F, D> = Marked<VariablePoissonProcess<F>, D>;
+
+
+/// Marked Hawkes process with an exponential kernel, where the jump
+/// caused by an event depends on its mark through an impact function $\psi$:
+/// $$
+///     \lambda_t = \lambda_0 + \sum_{t_i < t} \alpha\psi(m_i) e^{-\beta(t-t_i)}
+/// $$
+/// The marks are drawn i.i.d. from the mark distribution.
+#[derive(Debug)]
+pub struct MarkedExpHawkes<D, F> {
+    model: ExpHawkes,
+    mark_dist: D,
+    impact: F
+}
+
+impl<D, F> MarkedExpHawkes<D, F> {
+    /// Create a new marked exponential Hawkes model instance.
+    pub fn new(alpha: f64, beta: f64, lambda0: f64, mark_dist: D, impact: F) -> Self {
+        let model = ExpHawkes::new(alpha, beta, lambda0);
+        MarkedExpHawkes {
+            model, mark_dist, impact
+        }
+    }
+
+    /// Cap the number of sampled events, as for
+    /// [`Hawkes::with_max_events`](super::hawkes::Hawkes::with_max_events).
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.model = self.model.with_max_events(max_events);
+        self
+    }
+
+    /// Get the maximum number of simulated events, if any.
+    pub fn get_max_events(&self) -> Option<usize> {
+        self.model.get_max_events()
+    }
+
+    /// Get the Hawkes kernel object.
+    pub fn get_kernel(&self) -> &ExpKernel {
+        self.model.get_kernel()
+    }
+
+    /// Get the background intensity.
+    pub fn get_background(&self) -> &ConstBackground {
+        self.model.get_background()
+    }
+
+    /// Get the mark distribution.
+    pub fn get_mark_dist(&self) -> &D {
+        &self.mark_dist
+    }
+}
+
+impl<D, F, M> MarkedTemporalProcess<M> for MarkedExpHawkes<D, F>
+where D: Distribution<M>, F: Fn(&M) -> f64
+{
+    /// Uses the linear-time recursive algorithm, the mark of each
+    /// accepted event being drawn before the intensity jump.
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> MarkedProcessResult<M>
+    {
+        let kernel = self.model.get_kernel();
+        let alpha = kernel.alpha;
+        let beta = kernel.beta;
+        let lambda0 = self.model.get_background().intensity(0.);
+        let impact = &self.impact;
+        let cap = self.model.event_cap();
+
+        let mut timestamps = Vec::new();
+        let mut intensities = Vec::new();
+        let mut marks = Vec::new();
+
+        let mut max_lbda = lambda0;
+        let mut s = 0.;
+        let mut cur_slbda = 0.;  // current self-exciting intensity
+
+        while s < tmax && !cap.reached(timestamps.len()) {
+            let u: f64 = rng.gen();
+            // candidate next event time
+            let ds = -u.ln()/max_lbda;
+            s += ds;
+            if s > tmax {
+                // end sampling
+                break;
+            }
+
+            // decay the self-exciting part
+            cur_slbda *= (-beta * ds).exp();
+            let cur_lbda = lambda0 + cur_slbda;  // total intensity
+
+            // rejection sampling step
+            let d: f64 = rng.gen();
+            if d * max_lbda < cur_lbda {
+                // accept the candidate event time and draw its mark
+                let mark = self.mark_dist.sample(rng);
+                cur_slbda += alpha * impact(&mark);
+                timestamps.push(s);
+                intensities.push(lambda0 + cur_slbda);
+                marks.push(mark);
+            }
+            max_lbda = lambda0 + cur_slbda;  // update max intensity
+        }
+
+        let timestamps = Array1::from_vec(timestamps);
+        let intensities = Array1::from_vec(intensities);
+
+        MarkedProcessResult {
+            timestamps, intensities, marks
+        }
+    }
+}
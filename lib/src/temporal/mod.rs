@@ -7,13 +7,18 @@ pub mod traits;
 pub mod poisson;
 pub mod cox;
 pub mod hawkes;
+pub mod multivariate;
+pub mod marked;
+pub mod compoundpoisson;
+pub mod renewal;
+pub mod etas;
 pub mod utils;
 
 /// Reexport traits 
 pub use traits::*;
 
 pub use poisson::*;
-pub use utils::simulate_brownian;
+pub use utils::{simulate_brownian, simulate_ornstein_uhlenbeck};
 
 use ndarray::prelude::*;
 
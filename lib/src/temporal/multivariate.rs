@@ -0,0 +1,200 @@
+//! Multivariate, mutually exciting Hawkes processes.
+use super::utils::EventCap;
+use rand::prelude::*;
+
+use ndarray::prelude::*;
+
+use crate::error::{PointProcessError, check_positive, check_nonnegative};
+
+
+/// Result type for multivariate temporal processes.
+/// Event timestamps, the dimension each event belongs to,
+/// and the values of the intensity vector right after each event.
+#[derive(Debug)]
+pub struct MultiProcessResult {
+    pub timestamps: Array1<f64>,
+    pub dimensions: Array1<usize>,
+    /// Intensities of every component, one row per event.
+    pub intensities: Array2<f64>
+}
+
+impl MultiProcessResult {
+    /// Get the event timestamps of the `k`-th component.
+    pub fn component(&self, k: usize) -> Array1<f64> {
+        self.timestamps.iter()
+            .zip(self.dimensions.iter())
+            .filter(|(_, &d)| d == k)
+            .map(|(t, _)| *t)
+            .collect()
+    }
+}
+
+/// $D$-dimensional Hawkes process with exponential kernels.
+/// The intensity of the $i$-th component is
+/// $$
+///     \lambda^i_t = \mu_i + \sum_{j=1}^D \int_0^t
+///     \alpha_{ij} e^{-\beta_{ij}(t-s)} dN^j_s
+/// $$
+/// so that $\alpha_{ij}$ is the jump in the intensity of component $i$
+/// caused by an event of component $j$.
+#[derive(Debug)]
+pub struct MultivariateExpHawkes {
+    /// Background intensities.
+    mu: Array1<f64>,
+    /// Jump sizes.
+    alpha: Array2<f64>,
+    /// Decay rates.
+    beta: Array2<f64>,
+    /// Maximum number of events to simulate.
+    max_events: EventCap
+}
+
+impl MultivariateExpHawkes {
+    /// Create a new multivariate Hawkes model instance.
+    pub fn new(mu: Array1<f64>, alpha: Array2<f64>, beta: Array2<f64>) -> Self {
+        Self::try_new(mu, alpha, beta)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new multivariate Hawkes model, checking that the jump size
+    /// and decay rate matrices are square with the dimension of the background,
+    /// that the background intensities and jump sizes are nonnegative and finite,
+    /// and that the decay rates are positive and finite.
+    pub fn try_new(mu: Array1<f64>, alpha: Array2<f64>, beta: Array2<f64>) -> Result<Self, PointProcessError> {
+        let dim = mu.len();
+        if dim == 0 {
+            return Err(PointProcessError::InvalidParameter(
+                "a multivariate Hawkes process needs at least one component".to_string()));
+        }
+        for (name, dims) in [("alpha", alpha.dim()), ("beta", beta.dim())].iter() {
+            if *dims != (dim, dim) {
+                return Err(PointProcessError::InvalidParameter(
+                    format!("{} has shape {:?}, expected ({}, {})", name, dims, dim, dim)));
+            }
+        }
+        for m in mu.iter() {
+            check_nonnegative("mu", *m)?;
+        }
+        for a in alpha.iter() {
+            check_nonnegative("alpha", *a)?;
+        }
+        for b in beta.iter() {
+            check_positive("beta", *b)?;
+        }
+
+        Ok(MultivariateExpHawkes {
+            mu, alpha, beta, max_events: EventCap::default()
+        })
+    }
+
+    /// Cap the total number of sampled events over all the components,
+    /// as for [`Hawkes::with_max_events`](super::hawkes::Hawkes::with_max_events).
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.max_events = EventCap(Some(max_events));
+        self
+    }
+
+    /// Get the maximum number of simulated events, if any.
+    pub fn get_max_events(&self) -> Option<usize> {
+        self.max_events.0
+    }
+
+    /// Dimension $D$ of the process.
+    pub fn dim(&self) -> usize {
+        self.mu.len()
+    }
+
+    /// Get the background intensities $\mu$.
+    pub fn get_background(&self) -> &Array1<f64> {
+        &self.mu
+    }
+
+    /// Get the jump size matrix $\alpha$.
+    pub fn get_alpha(&self) -> &Array2<f64> {
+        &self.alpha
+    }
+
+    /// Get the decay rate matrix $\beta$.
+    pub fn get_beta(&self) -> &Array2<f64> {
+        &self.beta
+    }
+
+    /// Sample a sequence of events of the process, using Ogata's
+    /// thinning algorithm on the total intensity.
+    /// Each pair $(i,j)$ keeps a recursively updated excitation
+    /// state, which makes the algorithm linear in the number of events.
+    pub fn sample(&self, tmax: f64) -> MultiProcessResult {
+        self.sample_with_rng(tmax, &mut thread_rng())
+    }
+
+    /// Sample a sequence of events of the process, using the
+    /// supplied random number generator.
+    pub fn sample_with_rng<R>(&self, tmax: f64, rng: &mut R) -> MultiProcessResult
+    where R: Rng + ?Sized
+    {
+        let dim = self.dim();
+        let mu = &self.mu;
+        let alpha = &self.alpha;
+        let beta = &self.beta;
+
+        let mut timestamps = Vec::new();
+        let mut dimensions = Vec::new();
+        let mut intensities = Vec::new();
+
+        // excitation of component i by the past events of component j
+        let mut states: Array2<f64> = Array2::zeros((dim, dim));
+        let mut cur_lbdas: Array1<f64>;
+        let mut max_lbda = mu.sum();
+        let mut s = 0.;
+
+        while s < tmax && !self.max_events.reached(timestamps.len()) {
+            let u: f64 = rng.gen();
+            // candidate next event time
+            let ds = -u.ln()/max_lbda;
+            s += ds;
+            if s > tmax {
+                // end sampling
+                break;
+            }
+
+            // decay the self-exciting parts
+            states.zip_mut_with(beta, |st, b| {
+                *st *= (-b * ds).exp();
+            });
+            cur_lbdas = mu + &states.sum_axis(Axis(1));
+            let cur_lbda = cur_lbdas.sum();  // total intensity
+
+            // rejection sampling step
+            let d: f64 = rng.gen();
+            if d * max_lbda < cur_lbda {
+                // attribute the event to a component
+                let v = rng.gen::<f64>() * cur_lbda;
+                let mut k = 0;
+                let mut acc = cur_lbdas[0];
+                while acc < v && k < dim - 1 {
+                    k += 1;
+                    acc += cur_lbdas[k];
+                }
+                // add the jumps caused by component k
+                let mut col = states.column_mut(k);
+                col += &alpha.column(k);
+                cur_lbdas = mu + &states.sum_axis(Axis(1));
+
+                timestamps.push(s);
+                dimensions.push(k);
+                intensities.extend(cur_lbdas.iter());
+            }
+            // the kernels decrease, so the current intensity is a bound
+            max_lbda = cur_lbdas.sum();
+        }
+
+        let num_events = timestamps.len();
+        let timestamps = Array1::from_vec(timestamps);
+        let dimensions = Array1::from_vec(dimensions);
+        let intensities = Array2::from_shape_vec((num_events, dim), intensities).unwrap();
+
+        MultiProcessResult {
+            timestamps, dimensions, intensities
+        }
+    }
+}
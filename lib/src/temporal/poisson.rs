@@ -1,7 +1,6 @@
 //! Poisson processes.
 use super::traits::*;
 use rand::prelude::*;
-use rand_distr::{Uniform, Poisson, Distribution};
 
 use ndarray::array;
 use ndarray::prelude::*;
@@ -9,6 +8,12 @@ use ndarray_parallel::prelude::*;
 
 use rayon::prelude::*;
 
+use super::utils::{sample_poisson_count, bisect, PiecewiseTable};
+pub use super::utils::Interpolation;
+use crate::error::{PointProcessError, check_nonnegative, check_positive};
+
+static POISSON_MEAN_ERR: &str = "Intensity and time window must be nonnegative and finite";
+
 
 /// Homogeneous, constant intensity Poisson process.
 /// The intensity of the process is given by the average
@@ -29,6 +34,13 @@ impl PoissonProcess {
             lambda
         }
     }
+
+    /// Create a new Poisson process, checking that the intensity
+    /// is nonnegative and finite.
+    pub fn try_new(lambda: f64) -> Result<Self, PointProcessError> {
+        check_nonnegative("lambda", lambda)?;
+        Ok(Self::new(lambda))
+    }
 }
 
 impl DeterministicIntensity for PoissonProcess {
@@ -37,6 +49,12 @@ impl DeterministicIntensity for PoissonProcess {
     }
 }
 
+impl BoundedIntensity for PoissonProcess {
+    fn max_intensity(&self) -> f64 {
+        self.lambda
+    }
+}
+
 /// Poisson process with variable intensity.
 /// The average number of events between $t$ and $t+dt$ is
 /// $$ \mathbb{E}[ dN_t ] = \lambda(t) dt $$
@@ -61,6 +79,13 @@ where F: Fn(f64) -> f64 + Send + Sync
         }
     }
 
+    /// Create a new variable Poisson process, checking that the upper bound
+    /// on the intensity is nonnegative and finite.
+    pub fn try_new(func: F, max_lambda: f64) -> Result<Self, PointProcessError> {
+        check_nonnegative("max_lambda", max_lambda)?;
+        Ok(Self::new(func, max_lambda))
+    }
+
     /// Get the max_lambda upper bound on the intensity.
     pub fn get_max_lambda(&self) -> f64 {
         self.max_lambda
@@ -75,23 +100,27 @@ where F: Fn(f64) -> f64 + Send + Sync
     }
 }
 
+impl<F> BoundedIntensity for VariablePoissonProcess<F>
+where F: Fn(f64) -> f64 + Send + Sync
+{
+    fn max_intensity(&self) -> f64 {
+        self.max_lambda
+    }
+}
+
 
 impl TemporalProcess for PoissonProcess {
-    fn sample(&self, tmax: f64) -> TimeProcessResult {
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult
+    {
         let lambda = self.lambda;
-        let mut rng = thread_rng();
-        let fish = Poisson::new(tmax * lambda).unwrap();
-        let num_events: u64 = fish.sample(&mut rng);
+        let num_events = sample_poisson_count(tmax * lambda, rng)
+            .expect(POISSON_MEAN_ERR);
         let num_events = num_events as usize;
         
-        let mut events_vec: Vec<_> = (0..num_events).into_par_iter()
-            .map(|_| {
-                // get reference to local thread rng
-                let mut rng = thread_rng();
-                let u = Uniform::new(0.0, tmax);
-                u.sample(&mut rng)
-        }).collect();
-        events_vec.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mut events_vec: Vec<f64> = (0..num_events)
+            .map(|_| rng.gen::<f64>() * tmax)
+            .collect();
+        events_vec.sort_by(|a, b| a.total_cmp(b));
         let timestamps = Array1::<f64>::from_vec(events_vec);
         let mut intensities = Array1::<f64>::zeros(num_events as usize);
         for i in 0..num_events as usize {
@@ -106,34 +135,37 @@ impl TemporalProcess for PoissonProcess {
 impl<F> TemporalProcess for VariablePoissonProcess<F>
 where F: Fn(f64) -> f64 + Send + Sync
 {
-    fn sample(&self, tmax: f64) -> TimeProcessResult {
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult
+    {
         // Parallelized, multithreaded algorithm for sampling
-        // from the process.
+        // from the process: the candidates are drawn sequentially from the rng,
+        // the intensity is evaluated in parallel.
 
-        let mut rng = thread_rng();
         let max_lambda = self.max_lambda;
         let lambda = &self.func;
-        let fish = Poisson::new(tmax*max_lambda).unwrap();
-        let num_events: u64 = fish.sample(&mut rng);
+        let num_events = sample_poisson_count(tmax * max_lambda, rng)
+            .expect(POISSON_MEAN_ERR);
         let num_events = num_events as usize;
-        let lambda = std::sync::Arc::from(lambda);
-        
+
         // Get timestamp and intensity values of events distributed
         // according to a homogeneous Poisson process
-        // and keep those who are under the intensity curve
-        let mut events: Vec<Array1<f64>> = (0..num_events)
-                .into_par_iter().filter_map(|_| {
-            let mut rng = thread_rng();
+        let candidates: Vec<(f64, f64)> = (0..num_events).map(|_| {
             let timestamp = rng.gen::<f64>()*tmax;
             let lambda_val = rng.gen::<f64>()*max_lambda;
+            (timestamp, lambda_val)
+        }).collect();
 
-            if lambda_val < lambda(timestamp) {
-                Some(array![timestamp, lambda(timestamp)])
+        // and keep those who are under the intensity curve
+        let mut events: Vec<Array1<f64>> = candidates
+                .into_par_iter().filter_map(|(timestamp, lambda_val)| {
+            let intensity = lambda(timestamp);
+            if lambda_val < intensity {
+                Some(array![timestamp, intensity])
             } else {
                 None
             }
         }).collect();
-        events.sort_by(|a, b| a[0].partial_cmp(&b[0]).unwrap());
+        events.sort_by(|a, b| a[0].total_cmp(&b[0]));
 
         let num_events = events.len();
 
@@ -149,3 +181,306 @@ where F: Fn(f64) -> f64 + Send + Sync
         TimeProcessResult { timestamps, intensities }
     }
 }
+
+
+/// Poisson process with a piecewise-constant or piecewise-linear intensity,
+/// given by its values $\lambda(t_k) = v_k$ at the nodes $t_0 < \cdots < t_m$
+/// and interpolated in between.
+/// The intensity vanishes outside of $[t_0, t_m[$.
+///
+/// The process is sampled exactly, by inverting the integrated intensity
+/// segment by segment.
+#[derive(Debug,Clone)]
+pub struct PiecewisePoissonProcess {
+    table: PiecewiseTable
+}
+
+impl PiecewisePoissonProcess {
+    pub fn new(nodes: Vec<f64>, values: Vec<f64>, interpolation: Interpolation) -> Self {
+        Self::try_new(nodes, values, interpolation)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new piecewise Poisson process, checking that there are
+    /// as many values as nodes, at least two increasing nodes,
+    /// and that the values are nonnegative and finite.
+    pub fn try_new(nodes: Vec<f64>, values: Vec<f64>, interpolation: Interpolation) -> Result<Self, PointProcessError> {
+        for v in values.iter() {
+            check_nonnegative("intensity value", *v)?;
+        }
+        let table = PiecewiseTable::try_new(nodes, values, interpolation)?;
+        Ok(PiecewisePoissonProcess { table })
+    }
+
+    /// Get the nodes.
+    pub fn get_nodes(&self) -> &[f64] {
+        &self.table.nodes
+    }
+
+    /// Get the values of the intensity at the nodes.
+    pub fn get_values(&self) -> &[f64] {
+        &self.table.values
+    }
+
+    /// Get the interpolation scheme.
+    pub fn get_interpolation(&self) -> Interpolation {
+        self.table.interpolation
+    }
+
+    /// Integrated intensity $\Lambda(t) = \int_0^t \lambda(s)ds$.
+    pub fn integrated_intensity(&self, t: f64) -> f64 {
+        self.table.integral(0., t)
+    }
+
+    /// Length $u$ such that the integral of the intensity over $[a, a + u]$,
+    /// within the segment $k$, is `mass`. It solves
+    /// $\lambda(a) u + su^2/2 = m$ with $s$ the slope, in a form which is
+    /// stable when $s$ vanishes.
+    fn invert_segment(&self, k: usize, a: f64, mass: f64) -> f64 {
+        let v0 = self.table.eval_segment(k, a);
+        let slope = self.table.slope(k);
+        let disc = (v0 * v0 + 2. * slope * mass).max(0.);
+        2. * mass / (v0 + disc.sqrt())
+    }
+}
+
+impl DeterministicIntensity for PiecewisePoissonProcess {
+    fn intensity(&self, t: f64) -> f64 {
+        self.table.eval(t)
+    }
+}
+
+impl BoundedIntensity for PiecewisePoissonProcess {
+    /// The interpolated intensity reaches its maximum at one of the nodes.
+    fn max_intensity(&self) -> f64 {
+        self.table.values.iter().fold(0., |acc: f64, v| acc.max(*v))
+    }
+}
+
+impl TemporalProcess for PiecewisePoissonProcess {
+    /// The gaps between events are i.i.d. $\mathrm{Exp}(1)$ in the time scale
+    /// of the integrated intensity: the mass left before the next event is drawn,
+    /// then consumed segment by segment until it falls within a segment, where
+    /// the integrated intensity is inverted in closed form.
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult
+    {
+        let mut timestamps = Vec::new();
+        let mut intensities = Vec::new();
+
+        let table = &self.table;
+        let mut mass: f64 = -rng.gen::<f64>().ln();
+        for k in 0..table.num_segments() {
+            let end = table.nodes[k+1].min(tmax);
+            let mut start = table.nodes[k].max(0.);
+            while start < end {
+                let seg_mass = table.segment_integral(k, start, end);
+                if mass > seg_mass {
+                    mass -= seg_mass;
+                    break;
+                }
+                let t = (start + self.invert_segment(k, start, mass)).min(end);
+                timestamps.push(t);
+                intensities.push(table.eval_segment(k, t));
+                start = t;
+                mass = -rng.gen::<f64>().ln();
+            }
+        }
+
+        TimeProcessResult {
+            timestamps: Array1::from_vec(timestamps),
+            intensities: Array1::from_vec(intensities)
+        }
+    }
+}
+
+/// Poisson process with a general intensity function, sampled by adaptive
+/// thinning: instead of a global bound on the intensity, the user provides
+/// an envelope function `envelope(a, b)` returning an upper bound of the
+/// intensity on the window $[a, b]$.
+/// Candidates are drawn window by window, with windows of length at most `horizon`,
+/// which keeps the rejection rate low for intensities with sharp peaks.
+#[derive(Debug)]
+pub struct EnvelopePoissonProcess<F, G>
+where F: Fn(f64) -> f64 + Send + Sync,
+      G: Fn(f64, f64) -> f64 + Send + Sync
+{
+    /// Process intensity function.
+    func: F,
+    /// Local upper bound on the intensity.
+    envelope: G,
+    /// Maximum length of the thinning windows.
+    horizon: f64
+}
+
+impl<F, G> EnvelopePoissonProcess<F, G>
+where F: Fn(f64) -> f64 + Send + Sync,
+      G: Fn(f64, f64) -> f64 + Send + Sync
+{
+    pub fn new(func: F, envelope: G, horizon: f64) -> Self {
+        EnvelopePoissonProcess {
+            func, envelope, horizon
+        }
+    }
+
+    /// Create a new envelope Poisson process, checking that the window
+    /// length is positive and finite.
+    pub fn try_new(func: F, envelope: G, horizon: f64) -> Result<Self, PointProcessError> {
+        check_positive("horizon", horizon)?;
+        Ok(Self::new(func, envelope, horizon))
+    }
+
+    /// Get the maximum length of the thinning windows.
+    pub fn get_horizon(&self) -> f64 {
+        self.horizon
+    }
+}
+
+impl<F, G> DeterministicIntensity for EnvelopePoissonProcess<F, G>
+where F: Fn(f64) -> f64 + Send + Sync,
+      G: Fn(f64, f64) -> f64 + Send + Sync
+{
+    fn intensity(&self, t: f64) -> f64 {
+        (self.func)(t)
+    }
+}
+
+impl<F, G> TemporalProcess for EnvelopePoissonProcess<F, G>
+where F: Fn(f64) -> f64 + Send + Sync,
+      G: Fn(f64, f64) -> f64 + Send + Sync
+{
+    /// When a candidate falls beyond the current window, sampling restarts
+    /// from the end of the window with a new bound, which is valid since
+    /// exponential waiting times are memoryless.
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult
+    {
+        let mut timestamps = Vec::new();
+        let mut intensities = Vec::new();
+
+        let mut t = 0.;
+        while t < tmax {
+            let end = (t + self.horizon).min(tmax);
+            let bound = (self.envelope)(t, end);
+            if bound <= 0. {
+                t = end;
+                continue;
+            }
+            let candidate = t - rng.gen::<f64>().ln() / bound;
+            if candidate >= end {
+                t = end;
+                continue;
+            }
+            t = candidate;
+            let lambda = (self.func)(t);
+            debug_assert!(lambda <= bound, "intensity exceeds its envelope at {}", t);
+            if rng.gen::<f64>() * bound < lambda {
+                timestamps.push(t);
+                intensities.push(lambda);
+            }
+        }
+
+        TimeProcessResult {
+            timestamps: Array1::from_vec(timestamps),
+            intensities: Array1::from_vec(intensities)
+        }
+    }
+}
+
+
+/// Poisson process given by its cumulative intensity
+/// $$ \Lambda(t) = \Lambda(0) + \int_0^t \lambda(s)ds $$
+/// sampled by time change: if $(s_i)$ is a unit-rate Poisson process on
+/// $[\Lambda(0), \Lambda(T)]$, then the times $t_i = \Lambda^{-1}(s_i)$ are the events
+/// of the process on $[0, T]$. No bound on the intensity is needed, so
+/// the intensity can be unbounded, e.g. near 0.
+///
+/// When the inverse $\Lambda^{-1}$ is not supplied, it is computed
+/// by bisection.
+#[derive(Debug)]
+pub struct TimeChangePoissonProcess<F, G = fn(f64) -> f64>
+where F: Fn(f64) -> f64 + Send + Sync,
+      G: Fn(f64) -> f64 + Send + Sync
+{
+    /// Cumulative intensity function.
+    cumulative: F,
+    /// Inverse of the cumulative intensity.
+    inverse: Option<G>
+}
+
+impl<F> TimeChangePoissonProcess<F>
+where F: Fn(f64) -> f64 + Send + Sync
+{
+    /// Create a new time-change Poisson process from its cumulative
+    /// intensity only, which is inverted numerically.
+    pub fn from_cumulative(cumulative: F) -> Self {
+        TimeChangePoissonProcess {
+            cumulative,
+            inverse: None
+        }
+    }
+}
+
+impl<F, G> TimeChangePoissonProcess<F, G>
+where F: Fn(f64) -> f64 + Send + Sync,
+      G: Fn(f64) -> f64 + Send + Sync
+{
+    /// Create a new time-change Poisson process from its cumulative
+    /// intensity and the inverse function.
+    pub fn new(cumulative: F, inverse: G) -> Self {
+        TimeChangePoissonProcess {
+            cumulative,
+            inverse: Some(inverse)
+        }
+    }
+
+    /// Evaluate the cumulative intensity $\Lambda(t)$.
+    pub fn cumulative_intensity(&self, t: f64) -> f64 {
+        (self.cumulative)(t)
+    }
+
+    /// Evaluate the inverse $\Lambda^{-1}(s)$, for a time in $[0, T]$.
+    fn invert(&self, s: f64, lower: f64, tmax: f64) -> f64 {
+        match &self.inverse {
+            Some(inverse) => inverse(s),
+            None => bisect(&self.cumulative, s, lower, tmax)
+        }
+    }
+}
+
+impl<F, G> DeterministicIntensity for TimeChangePoissonProcess<F, G>
+where F: Fn(f64) -> f64 + Send + Sync,
+      G: Fn(f64) -> f64 + Send + Sync
+{
+    /// The intensity is the derivative of the cumulative intensity,
+    /// approximated by a finite difference.
+    fn intensity(&self, t: f64) -> f64 {
+        let h = 1e-6 * t.abs().max(1.);
+        if t > h {
+            ((self.cumulative)(t + h) - (self.cumulative)(t - h)) / (2. * h)
+        } else {
+            ((self.cumulative)(t + h) - (self.cumulative)(t)) / h
+        }
+    }
+}
+
+impl<F, G> TemporalProcess for TimeChangePoissonProcess<F, G>
+where F: Fn(f64) -> f64 + Send + Sync,
+      G: Fn(f64) -> f64 + Send + Sync
+{
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult
+    {
+        // the cumulative intensity need not vanish at 0
+        let start = self.cumulative_intensity(0.);
+        let unit_rate = PoissonProcess::new(1.);
+        let rescaled = unit_rate.sample_with_rng(self.cumulative_intensity(tmax) - start, rng);
+
+        // the rescaled events are sorted, so each root lies after the previous one
+        let mut last = 0.;
+        let timestamps = rescaled.timestamps.mapv(|s| {
+            last = self.invert(start + s, last, tmax);
+            last
+        });
+        let intensities = timestamps.mapv(|t| self.intensity(t));
+
+        TimeProcessResult { timestamps, intensities }
+    }
+}
@@ -0,0 +1,427 @@
+//! Renewal processes.
+//! The inter-arrival times $\tau_i = t_i - t_{i-1}$ are i.i.d. with density $f$
+//! and survival function $S$, so that the conditional intensity is the hazard
+//! rate of the time elapsed since the last event:
+//! $$
+//!     \lambda_t = h(t - t_{N_t}) = \frac{f(t - t_{N_t})}{S(t - t_{N_t})}
+//! $$
+//! With a stationary (equilibrium) start, the time to the first event has
+//! density $S(t)/\mu$, where $\mu$ is the mean inter-arrival time.
+use super::traits::*;
+use super::utils::{bisect, ln_gamma, upper_gamma_regularized};
+use rand::prelude::*;
+use rand_distr::Distribution;
+
+use ndarray::prelude::*;
+
+use crate::error::{PointProcessError, check_positive};
+
+static RENEWAL_PARAM_ERR: &str = "Invalid inter-arrival distribution parameters";
+
+
+/// Inter-arrival distribution of a renewal process, on $]0,\infty[$.
+pub trait InterArrival: Distribution<f64> {
+    /// Probability density $f(t)$.
+    fn pdf(&self, t: f64) -> f64;
+
+    /// Survival function $S(t) = \mathbb P(\tau > t)$.
+    fn survival(&self, t: f64) -> f64;
+
+    /// Mean inter-arrival time $\mu$, possibly infinite.
+    fn mean(&self) -> f64;
+
+    /// Survival function of the equilibrium (forward recurrence) distribution
+    /// $$ S_e(t) = \frac1\mu \int_t^\infty S(s) ds $$
+    fn equilibrium_survival(&self, t: f64) -> f64;
+
+    /// Hazard rate $h(t) = f(t)/S(t)$.
+    fn hazard(&self, t: f64) -> f64 {
+        self.pdf(t) / self.survival(t)
+    }
+
+    /// Hazard rate of the equilibrium distribution, $S(t)/(\mu S_e(t))$.
+    fn equilibrium_hazard(&self, t: f64) -> f64 {
+        self.survival(t) / (self.mean() * self.equilibrium_survival(t))
+    }
+
+    /// Sample from the equilibrium distribution, by inverting
+    /// its survival function with bisection.
+    fn sample_equilibrium<R>(&self, rng: &mut R) -> f64
+    where R: Rng + ?Sized
+    {
+        let u = 1. - rng.gen::<f64>();
+        let mut upper = self.mean();
+        while upper.is_finite() && self.equilibrium_survival(upper) > u {
+            upper *= 2.;
+        }
+        bisect(|t| -self.equilibrium_survival(t), -u, 0., upper)
+    }
+}
+
+/// Survival function of the standard normal distribution.
+fn normal_survival(x: f64) -> f64 {
+    let tail = 0.5 * upper_gamma_regularized(0.5, 0.5 * x * x);
+    if x >= 0. {
+        tail
+    } else {
+        1. - tail
+    }
+}
+
+/// Gamma inter-arrival times with density
+/// $$ f(t) = \frac{t^{k-1}e^{-t/\theta}}{\Gamma(k)\theta^k} $$
+#[derive(Debug,Clone)]
+pub struct GammaInterArrival {
+    shape: f64,
+    scale: f64,
+    dist: rand_distr::Gamma<f64>
+}
+
+impl GammaInterArrival {
+    pub fn new(shape: f64, scale: f64) -> Self {
+        Self::try_new(shape, scale).expect(RENEWAL_PARAM_ERR)
+    }
+
+    /// Create a new Gamma distribution, checking that the shape $k$
+    /// and scale $\theta$ are positive and finite.
+    pub fn try_new(shape: f64, scale: f64) -> Result<Self, PointProcessError> {
+        check_positive("shape", shape)?;
+        check_positive("scale", scale)?;
+        let dist = rand_distr::Gamma::new(shape, scale).map_err(|_| {
+            PointProcessError::InvalidParameter(RENEWAL_PARAM_ERR.to_string())
+        })?;
+        Ok(GammaInterArrival { shape, scale, dist })
+    }
+}
+
+impl Distribution<f64> for GammaInterArrival {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.dist.sample(rng)
+    }
+}
+
+impl InterArrival for GammaInterArrival {
+    fn pdf(&self, t: f64) -> f64 {
+        if t < 0. {
+            return 0.;
+        }
+        let (k, theta) = (self.shape, self.scale);
+        if t == 0. {
+            return if k < 1. {
+                f64::INFINITY
+            } else if k == 1. {
+                1. / theta
+            } else {
+                0.
+            };
+        }
+        ((k - 1.) * t.ln() - t / theta - ln_gamma(k) - k * theta.ln()).exp()
+    }
+
+    fn survival(&self, t: f64) -> f64 {
+        if t <= 0. {
+            1.
+        } else {
+            upper_gamma_regularized(self.shape, t / self.scale)
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        self.shape * self.scale
+    }
+
+    /// Uses $\mathbb E[\min(\tau, t)] = tS(t) + \mu P(k+1, t/\theta)$.
+    fn equilibrium_survival(&self, t: f64) -> f64 {
+        if t <= 0. {
+            return 1.;
+        }
+        let res = upper_gamma_regularized(self.shape + 1., t / self.scale)
+            - t * self.survival(t) / self.mean();
+        res.max(0.)
+    }
+}
+
+/// Weibull inter-arrival times with survival function
+/// $$ S(t) = e^{-(t/\lambda)^k} $$
+#[derive(Debug,Clone)]
+pub struct WeibullInterArrival {
+    scale: f64,
+    shape: f64,
+    dist: rand_distr::Weibull<f64>
+}
+
+impl WeibullInterArrival {
+    pub fn new(scale: f64, shape: f64) -> Self {
+        Self::try_new(scale, shape).expect(RENEWAL_PARAM_ERR)
+    }
+
+    /// Create a new Weibull distribution, checking that the scale $\lambda$
+    /// and shape $k$ are positive and finite.
+    pub fn try_new(scale: f64, shape: f64) -> Result<Self, PointProcessError> {
+        check_positive("scale", scale)?;
+        check_positive("shape", shape)?;
+        let dist = rand_distr::Weibull::new(scale, shape).map_err(|_| {
+            PointProcessError::InvalidParameter(RENEWAL_PARAM_ERR.to_string())
+        })?;
+        Ok(WeibullInterArrival { scale, shape, dist })
+    }
+}
+
+impl Distribution<f64> for WeibullInterArrival {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.dist.sample(rng)
+    }
+}
+
+impl InterArrival for WeibullInterArrival {
+    fn pdf(&self, t: f64) -> f64 {
+        if t < 0. {
+            return 0.;
+        }
+        self.hazard(t) * self.survival(t)
+    }
+
+    fn survival(&self, t: f64) -> f64 {
+        if t <= 0. {
+            1.
+        } else {
+            (-(t / self.scale).powf(self.shape)).exp()
+        }
+    }
+
+    /// Closed form $h(t) = \frac{k}{\lambda}(t/\lambda)^{k-1}$, which
+    /// is accurate far in the tail.
+    fn hazard(&self, t: f64) -> f64 {
+        let (lbda, k) = (self.scale, self.shape);
+        k / lbda * (t / lbda).powf(k - 1.)
+    }
+
+    fn mean(&self) -> f64 {
+        self.scale * ln_gamma(1. + 1. / self.shape).exp()
+    }
+
+    /// Uses $\mathbb E[\min(\tau, t)] = tS(t) + \mu P(1 + 1/k, (t/\lambda)^k)$.
+    fn equilibrium_survival(&self, t: f64) -> f64 {
+        if t <= 0. {
+            return 1.;
+        }
+        let x = (t / self.scale).powf(self.shape);
+        let res = upper_gamma_regularized(1. + 1. / self.shape, x)
+            - t * self.survival(t) / self.mean();
+        res.max(0.)
+    }
+}
+
+/// Log-normal inter-arrival times: $\ln\tau \sim \mathcal N(m, \sigma^2)$.
+#[derive(Debug,Clone)]
+pub struct LogNormalInterArrival {
+    mu: f64,
+    sigma: f64,
+    dist: rand_distr::LogNormal<f64>
+}
+
+impl LogNormalInterArrival {
+    pub fn new(mu: f64, sigma: f64) -> Self {
+        Self::try_new(mu, sigma).expect(RENEWAL_PARAM_ERR)
+    }
+
+    /// Create a new log-normal distribution, checking that $m$ is finite
+    /// and $\sigma$ positive and finite.
+    pub fn try_new(mu: f64, sigma: f64) -> Result<Self, PointProcessError> {
+        if !mu.is_finite() {
+            return Err(PointProcessError::InvalidParameter(
+                format!("mu must be finite, got {}", mu)));
+        }
+        check_positive("sigma", sigma)?;
+        let dist = rand_distr::LogNormal::new(mu, sigma).map_err(|_| {
+            PointProcessError::InvalidParameter(RENEWAL_PARAM_ERR.to_string())
+        })?;
+        Ok(LogNormalInterArrival { mu, sigma, dist })
+    }
+}
+
+impl Distribution<f64> for LogNormalInterArrival {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.dist.sample(rng)
+    }
+}
+
+impl InterArrival for LogNormalInterArrival {
+    fn pdf(&self, t: f64) -> f64 {
+        if t <= 0. {
+            return 0.;
+        }
+        let z = (t.ln() - self.mu) / self.sigma;
+        (-0.5 * z * z).exp() / (t * self.sigma * (2. * std::f64::consts::PI).sqrt())
+    }
+
+    fn survival(&self, t: f64) -> f64 {
+        if t <= 0. {
+            1.
+        } else {
+            normal_survival((t.ln() - self.mu) / self.sigma)
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        (self.mu + 0.5 * self.sigma * self.sigma).exp()
+    }
+
+    /// Uses $\mathbb E[\min(\tau, t)] = tS(t) + \mu\Phi((\ln t - m - \sigma^2)/\sigma)$.
+    fn equilibrium_survival(&self, t: f64) -> f64 {
+        if t <= 0. {
+            return 1.;
+        }
+        let z = (t.ln() - self.mu - self.sigma * self.sigma) / self.sigma;
+        let res = normal_survival(z) - t * self.survival(t) / self.mean();
+        res.max(0.)
+    }
+}
+
+/// Pareto inter-arrival times with survival function
+/// $$ S(t) = (t_m / t)^\alpha, \quad t \geq t_m $$
+/// The mean is finite only for $\alpha > 1$.
+#[derive(Debug,Clone)]
+pub struct ParetoInterArrival {
+    scale: f64,
+    shape: f64,
+    dist: rand_distr::Pareto<f64>
+}
+
+impl ParetoInterArrival {
+    pub fn new(scale: f64, shape: f64) -> Self {
+        Self::try_new(scale, shape).expect(RENEWAL_PARAM_ERR)
+    }
+
+    /// Create a new Pareto distribution, checking that the scale $t_m$
+    /// and shape $\alpha$ are positive and finite.
+    pub fn try_new(scale: f64, shape: f64) -> Result<Self, PointProcessError> {
+        check_positive("scale", scale)?;
+        check_positive("shape", shape)?;
+        let dist = rand_distr::Pareto::new(scale, shape).map_err(|_| {
+            PointProcessError::InvalidParameter(RENEWAL_PARAM_ERR.to_string())
+        })?;
+        Ok(ParetoInterArrival { scale, shape, dist })
+    }
+}
+
+impl Distribution<f64> for ParetoInterArrival {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.dist.sample(rng)
+    }
+}
+
+impl InterArrival for ParetoInterArrival {
+    fn pdf(&self, t: f64) -> f64 {
+        if t < self.scale {
+            0.
+        } else {
+            self.shape / t * self.survival(t)
+        }
+    }
+
+    fn survival(&self, t: f64) -> f64 {
+        if t <= self.scale {
+            1.
+        } else {
+            (self.scale / t).powf(self.shape)
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        if self.shape > 1. {
+            self.shape * self.scale / (self.shape - 1.)
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    fn equilibrium_survival(&self, t: f64) -> f64 {
+        let (tm, alpha) = (self.scale, self.shape);
+        if t <= tm {
+            1. - t / self.mean()
+        } else {
+            (tm / t).powf(alpha - 1.) / alpha
+        }
+    }
+}
+
+
+/// Renewal process with i.i.d. inter-arrival times drawn from `D`.
+/// By default, the process starts with an event at time 0, which is not
+/// part of the sampled events.
+#[derive(Debug)]
+pub struct RenewalProcess<D> {
+    dist: D,
+    stationary: bool
+}
+
+impl<D: InterArrival> RenewalProcess<D> {
+    pub fn new(dist: D) -> Self {
+        RenewalProcess {
+            dist,
+            stationary: false
+        }
+    }
+
+    /// Start the process in its stationary (equilibrium) regime.
+    /// Panics if the mean inter-arrival time is not finite.
+    pub fn with_stationary_start(self) -> Self {
+        self.try_with_stationary_start()
+            .expect("stationary start requires a finite mean inter-arrival time")
+    }
+
+    /// Same as [`RenewalProcess::with_stationary_start`], returning an error
+    /// if the mean inter-arrival time is not finite.
+    pub fn try_with_stationary_start(mut self) -> Result<Self, PointProcessError> {
+        check_positive("mean inter-arrival time", self.dist.mean())?;
+        self.stationary = true;
+        Ok(self)
+    }
+
+    /// Get the inter-arrival distribution.
+    pub fn get_dist(&self) -> &D {
+        &self.dist
+    }
+
+    /// Whether the process starts in its stationary regime.
+    pub fn is_stationary(&self) -> bool {
+        self.stationary
+    }
+
+    /// Conditional intensity at time `t`, given the time of the last
+    /// event before `t`, if any.
+    pub fn conditional_intensity(&self, t: f64, last_event: Option<f64>) -> f64 {
+        match last_event {
+            Some(last) => self.dist.hazard(t - last),
+            None if self.stationary => self.dist.equilibrium_hazard(t),
+            None => self.dist.hazard(t)
+        }
+    }
+}
+
+impl<D: InterArrival> TemporalProcess for RenewalProcess<D> {
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult
+    {
+        let mut timestamps = Vec::new();
+        let mut intensities = Vec::new();
+
+        let mut t = if self.stationary {
+            self.dist.sample_equilibrium(rng)
+        } else {
+            self.dist.sample(rng)
+        };
+        let mut last_event = None;
+        while t < tmax {
+            intensities.push(self.conditional_intensity(t, last_event));
+            timestamps.push(t);
+            last_event = Some(t);
+            t += self.dist.sample(rng);
+        }
+
+        TimeProcessResult {
+            timestamps: Array1::from_vec(timestamps),
+            intensities: Array1::from_vec(intensities)
+        }
+    }
+}
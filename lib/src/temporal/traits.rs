@@ -1,5 +1,10 @@
 use rayon::prelude::*;
 use ndarray::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use super::utils::derive_seeds;
+use crate::error::{PointProcessError, check_nonnegative};
 
 /// Result type for temporal processes.
 /// Event timestamps and values of the intensity
@@ -8,12 +13,43 @@ pub struct TimeProcessResult {
     pub intensities: Array1<f64>
 }
 
+/// Result type for marked temporal processes.
+/// Event timestamps, values of the intensity and event marks.
+#[derive(Debug)]
+pub struct MarkedProcessResult<M> {
+    pub timestamps: Array1<f64>,
+    pub intensities: Array1<f64>,
+    pub marks: Vec<M>
+}
+
 
 /// Time-dependent point process model.
 pub trait TemporalProcess {
+    /// Sample a sequence of events of the process, using the
+    /// supplied random number generator.
+    /// Returns: event timestamps and intensity process.
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> TimeProcessResult;
+
     /// Sample a sequence of events of the process.
     /// Returns: event timestamps and intensity process.
-    fn sample(&self, tmax: f64) -> TimeProcessResult;
+    fn sample(&self, tmax: f64) -> TimeProcessResult {
+        self.sample_with_rng(tmax, &mut thread_rng())
+    }
+
+    /// Sample a sequence of events of the process, using the supplied random
+    /// number generator, returning an error instead of panicking
+    /// if the time window is invalid.
+    fn try_sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> Result<TimeProcessResult, PointProcessError>
+    {
+        check_nonnegative("tmax", tmax)?;
+        Ok(self.sample_with_rng(tmax, rng))
+    }
+
+    /// Sample a sequence of events of the process, returning an error
+    /// instead of panicking if the time window is invalid.
+    fn try_sample(&self, tmax: f64) -> Result<TimeProcessResult, PointProcessError> {
+        self.try_sample_with_rng(tmax, &mut thread_rng())
+    }
 
     /// Batch-sample sequences from the model.
     fn batch_sample(&self, tmax: f64, num_batch: usize) -> Vec<TimeProcessResult>
@@ -24,6 +60,32 @@ pub trait TemporalProcess {
             self.sample(tmax)
         }).collect()
     }
+
+    /// Batch-sample sequences from the model, reproducibly.
+    /// Each sequence is sampled from its own random stream, seeded
+    /// from a master generator: the result only depends on `seed`,
+    /// not on how the batches are scheduled across threads.
+    fn batch_sample_seeded(&self, tmax: f64, num_batch: usize, seed: u64) -> Vec<TimeProcessResult>
+    where Self: std::marker::Sync
+    {
+        let seeds = derive_seeds(seed, num_batch);
+        seeds.into_par_iter().map(|s| {
+            let mut rng = StdRng::seed_from_u64(s);
+            self.sample_with_rng(tmax, &mut rng)
+        }).collect()
+    }
+}
+
+/// Time-dependent point process model where each event carries a mark of type `M`.
+pub trait MarkedTemporalProcess<M> {
+    /// Sample a sequence of marked events of the process, using the
+    /// supplied random number generator.
+    fn sample_with_rng(&self, tmax: f64, rng: &mut dyn RngCore) -> MarkedProcessResult<M>;
+
+    /// Sample a sequence of marked events of the process.
+    fn sample(&self, tmax: f64) -> MarkedProcessResult<M> {
+        self.sample_with_rng(tmax, &mut thread_rng())
+    }
 }
 
 use std::fmt;
@@ -42,5 +104,12 @@ pub trait DeterministicIntensity {
     fn intensity(&self, t: f64) -> f64;
 }
 
+/// Deterministic intensity with a known upper bound,
+/// as required by thinning algorithms.
+pub trait BoundedIntensity: DeterministicIntensity {
+    /// Upper bound on the intensity.
+    fn max_intensity(&self) -> f64;
+}
+
 /// Indicates the process has a stochastic intensity process;
 pub trait StochasticIntensity {}
@@ -1,13 +1,307 @@
-use rand::thread_rng;
+use rand::prelude::*;
+use rand::rngs::StdRng;
 use rand_distr::Distribution;
 use rand_distr::DistIter;
 use rand_distr::StandardNormal;
+use rand_distr::Poisson;
 
+use crate::error::PointProcessError;
+
+
+/// Derive `num` independent seeds from a master seed, to seed
+/// the random streams of parallel samplers.
+pub fn derive_seeds(seed: u64, num: usize) -> Vec<u64> {
+    let mut master = StdRng::seed_from_u64(seed);
+    (0..num).map(|_| master.gen()).collect()
+}
+
+/// Draw a Poisson-distributed number of events with mean `mean`.
+/// A zero mean yields no events.
+pub(crate) fn sample_poisson_count<R>(mean: f64, rng: &mut R) -> Result<u64, PointProcessError>
+where R: Rng + ?Sized
+{
+    if mean == 0. {
+        return Ok(0);
+    }
+    let fish = Poisson::new(mean).map_err(|_| {
+        PointProcessError::InvalidParameter(
+            format!("mean number of events must be nonnegative and finite, got {}", mean))
+    })?;
+    Ok(fish.sample(rng))
+}
+
+/// Number of Simpson subintervals used between two events
+/// when integrating an intensity function by quadrature.
+pub(crate) static QUADRATURE_STEPS: usize = 64;
+
+/// Composite Simpson rule for $\int_a^b f$ with `steps` subintervals.
+pub(crate) fn simpson<F>(f: F, a: f64, b: f64, steps: usize) -> f64
+where F: Fn(f64) -> f64
+{
+    let steps = steps + steps % 2;
+    let h = (b - a) / steps as f64;
+    let inner = (1..steps).fold(0., |acc, k| {
+        let weight = if k % 2 == 1 { 4. } else { 2. };
+        acc + weight * f(a + k as f64 * h)
+    });
+    h / 3. * (f(a) + inner + f(b))
+}
+
+/// Maximum number of bisection steps when solving for a root.
+pub(crate) static BISECTION_STEPS: usize = 100;
+
+/// Solve $f(t) = y$ for a nondecreasing function $f$ on $[a, b]$
+/// with $f(a) \leq y \leq f(b)$, by bisection.
+pub(crate) fn bisect<F>(f: F, y: f64, mut a: f64, mut b: f64) -> f64
+where F: Fn(f64) -> f64
+{
+    for _ in 0..BISECTION_STEPS {
+        let mid = 0.5 * (a + b);
+        if mid <= a || mid >= b {
+            break;
+        }
+        if f(mid) < y {
+            a = mid;
+        } else {
+            b = mid;
+        }
+    }
+    0.5 * (a + b)
+}
+
+/// Optional cap on the number of events drawn by a sampler,
+/// see [`Hawkes::with_max_events`](super::hawkes::Hawkes::with_max_events).
+#[derive(Debug,Clone,Copy,Default,PartialEq)]
+pub(crate) struct EventCap(pub(crate) Option<usize>);
+
+impl EventCap {
+    /// Whether `num_events` sampled events reach the cap.
+    pub(crate) fn reached(self, num_events: usize) -> bool {
+        self.remaining(num_events) == 0
+    }
+
+    /// Number of events which can still be sampled after `num_events`.
+    pub(crate) fn remaining(self, num_events: usize) -> usize {
+        match self.0 {
+            Some(max_events) => max_events.saturating_sub(num_events),
+            None => usize::MAX
+        }
+    }
+}
+
+/// Interpolation scheme between the nodes of a piecewise function.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Interpolation {
+    /// The function takes the value of the previous node.
+    Constant,
+    /// The function is linearly interpolated between the nodes.
+    Linear
+}
+
+/// Check that the nodes of a piecewise function are finite and increasing,
+/// and that its values are finite.
+fn check_table(nodes: &[f64], values: &[f64]) -> Result<(), PointProcessError> {
+    if !nodes.iter().all(|t| t.is_finite()) || !nodes.windows(2).all(|w| w[0] < w[1]) {
+        return Err(PointProcessError::InvalidParameter(
+            "the nodes must be finite and increasing".to_string()));
+    }
+    if !values.iter().all(|v| v.is_finite()) {
+        return Err(PointProcessError::InvalidParameter(
+            "the values must be finite".to_string()));
+    }
+    Ok(())
+}
+
+/// Function given by its values $f(t_k) = v_k$ at the nodes
+/// $t_0 < \cdots < t_m$, interpolated in between.
+/// The function vanishes outside of $[t_0, t_m[$.
+#[derive(Debug,Clone)]
+pub(crate) struct PiecewiseTable {
+    pub(crate) nodes: Vec<f64>,
+    pub(crate) values: Vec<f64>,
+    pub(crate) interpolation: Interpolation
+}
+
+impl PiecewiseTable {
+    /// Create a new table, checking that there are as many values as nodes,
+    /// at least two nodes, that the nodes are finite and increasing,
+    /// and that the values are finite.
+    pub(crate) fn try_new(nodes: Vec<f64>, values: Vec<f64>, interpolation: Interpolation) -> Result<Self, PointProcessError> {
+        if nodes.len() != values.len() {
+            return Err(PointProcessError::InvalidParameter(
+                format!("got {} nodes but {} values", nodes.len(), values.len())));
+        }
+        if nodes.len() < 2 {
+            return Err(PointProcessError::InvalidParameter(
+                "a piecewise function needs at least two nodes".to_string()));
+        }
+        check_table(&nodes, &values)?;
+        Ok(PiecewiseTable {
+            nodes, values, interpolation
+        })
+    }
+
+    /// Number of segments $m$.
+    pub(crate) fn num_segments(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    /// Last node $t_m$.
+    pub(crate) fn end(&self) -> f64 {
+        self.nodes[self.nodes.len() - 1]
+    }
+
+    /// Index $k$ of the segment $[t_k, t_{k+1}[$ containing `t`, if any.
+    pub(crate) fn segment(&self, t: f64) -> Option<usize> {
+        if t < self.nodes[0] || t >= self.end() {
+            return None;
+        }
+        let k = self.nodes.iter()
+            .skip(1)
+            .take_while(|node| **node <= t)
+            .count();
+        Some(k)
+    }
+
+    /// Slope of the function on the segment $k$.
+    pub(crate) fn slope(&self, k: usize) -> f64 {
+        match self.interpolation {
+            Interpolation::Constant => 0.,
+            Interpolation::Linear => {
+                (self.values[k+1] - self.values[k]) / (self.nodes[k+1] - self.nodes[k])
+            }
+        }
+    }
+
+    /// Value of the function at `t` within the segment $k$.
+    pub(crate) fn eval_segment(&self, k: usize, t: f64) -> f64 {
+        self.values[k] + self.slope(k) * (t - self.nodes[k])
+    }
+
+    /// Value of the function at `t`.
+    pub(crate) fn eval(&self, t: f64) -> f64 {
+        match self.segment(t) {
+            Some(k) => self.eval_segment(k, t),
+            None => 0.
+        }
+    }
+
+    /// Integral of the function over $[a, b]$, within the segment $k$.
+    pub(crate) fn segment_integral(&self, k: usize, a: f64, b: f64) -> f64 {
+        0.5 * (b - a) * (self.eval_segment(k, a) + self.eval_segment(k, b))
+    }
+
+    /// Exact integral of the function over $[a, b]$.
+    pub(crate) fn integral(&self, a: f64, b: f64) -> f64 {
+        (0..self.num_segments()).fold(0., |acc, k| {
+            let start = self.nodes[k].max(a);
+            let end = self.nodes[k+1].min(b);
+            if end > start {
+                acc + self.segment_integral(k, start, end)
+            } else {
+                acc
+            }
+        })
+    }
+
+    /// Upper bound of the function on $[t, \infty[$. The interpolated
+    /// function reaches its maximum over a segment at one of the segment's ends.
+    pub(crate) fn upper_bound(&self, t: f64) -> f64 {
+        let mut bound: f64 = 0.;
+        for k in 0..self.num_segments() {
+            let (t0, t1) = (self.nodes[k], self.nodes[k+1]);
+            if t1 <= t {
+                continue;
+            }
+            bound = bound.max(self.eval_segment(k, t0.max(t)));
+            if self.interpolation == Interpolation::Linear {
+                bound = bound.max(self.values[k+1]);
+            }
+        }
+        bound
+    }
+}
+
+/// Logarithm of the Gamma function (Lanczos approximation).
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    static COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7
+    ];
+    let x = x - 1.;
+    let t = x + 7.5;
+    let series = COEFFS.iter()
+        .skip(1)
+        .enumerate()
+        .fold(COEFFS[0], |acc, (i, c)| acc + c / (x + (i + 1) as f64));
+    0.5 * (2. * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// Regularized upper incomplete Gamma function $Q(a, x)$, computed
+/// by its series expansion for $x < a + 1$ and its continued fraction otherwise.
+pub(crate) fn upper_gamma_regularized(a: f64, x: f64) -> f64 {
+    let eps = 1e-14;
+    let prefactor = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1. {
+        let mut term = 1. / a;
+        let mut sum = term;
+        let mut ap = a;
+        for _ in 0..1000 {
+            ap += 1.;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * eps {
+                break;
+            }
+        }
+        (1. - sum * prefactor).max(0.)
+    } else {
+        // modified Lentz algorithm
+        let tiny = 1e-300;
+        let mut b = x + 1. - a;
+        let mut c = 1. / tiny;
+        let mut d = 1. / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1. / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.).abs() < eps {
+                break;
+            }
+        }
+        (prefactor * h).min(1.)
+    }
+}
 
 /// Simulate a brownian motion $(B_t)$ with a time step of $h$.
 pub fn simulate_brownian(h: f64, n: usize) -> Vec<f64> {
+    simulate_brownian_with_rng(h, n, &mut thread_rng())
+}
+
+/// Simulate a brownian motion $(B_t)$ with a time step of $h$,
+/// using the supplied random number generator.
+pub fn simulate_brownian_with_rng<R>(h: f64, n: usize, rng: &mut R) -> Vec<f64>
+where R: Rng + ?Sized
+{
     let sqr_h = h.sqrt();
-    let ref mut rng = thread_rng();
     let normal: StandardNormal = StandardNormal;
     let mut normal_its: DistIter<_,_,f64> = normal.sample_iter(rng);
 
@@ -23,3 +317,91 @@ pub fn simulate_brownian(h: f64, n: usize) -> Vec<f64> {
     wt
 }
 
+
+/// Simulate an Ornstein-Uhlenbeck process
+/// $$ dX_t = -\theta X_t dt + dW_t $$
+/// started at $X_0 = 0$, with a time step of $h$.
+/// Uses the exact Gaussian transition of the process.
+pub fn simulate_ornstein_uhlenbeck(theta: f64, h: f64, n: usize) -> Vec<f64> {
+    simulate_ornstein_uhlenbeck_with_rng(theta, h, n, &mut thread_rng())
+}
+
+/// Simulate an Ornstein-Uhlenbeck process with a time step of $h$,
+/// using the supplied random number generator.
+pub fn simulate_ornstein_uhlenbeck_with_rng<R>(theta: f64, h: f64, n: usize, rng: &mut R) -> Vec<f64>
+where R: Rng + ?Sized
+{
+    let decay = (-theta * h).exp();
+    let std_dev = ((1. - decay * decay) / (2. * theta)).sqrt();
+    let normal: StandardNormal = StandardNormal;
+
+    let mut xt: Vec<_> = vec![0.; n];
+    for i in 0..n-1 {
+        let z: f64 = normal.sample(rng);
+        xt[i+1] = decay * xt[i] + std_dev * z;
+    }
+    xt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rel_close(value: f64, expected: f64, rel_tol: f64) {
+        let err = ((value - expected) / expected).abs();
+        assert!(err < rel_tol, "got {}, expected {}", value, expected);
+    }
+
+    /// $Q(n, x) = e^{-x}\sum_{k<n} x^k/k!$ for integer $n$.
+    fn upper_gamma_integer(n: usize, x: f64) -> f64 {
+        let mut term = 1.;
+        let mut sum = 1.;
+        for k in 1..n {
+            term *= x / k as f64;
+            sum += term;
+        }
+        (-x).exp() * sum
+    }
+
+    #[test]
+    fn ln_gamma_known_values() {
+        // Gamma(1/2) = sqrt(pi)
+        assert_rel_close(ln_gamma(0.5), 0.5 * std::f64::consts::PI.ln(), 1e-12);
+        // Gamma(n) = (n-1)!
+        let mut factorial = 1.;
+        for n in 1..20 {
+            if n > 1 {
+                factorial *= (n - 1) as f64;
+            }
+            assert!((ln_gamma(n as f64) - factorial.ln()).abs() < 1e-10, "n = {}", n);
+        }
+        // Gamma(3/2) = sqrt(pi)/2
+        assert_rel_close(ln_gamma(1.5), (0.5 * std::f64::consts::PI.sqrt()).ln(), 1e-10);
+    }
+
+    #[test]
+    fn upper_gamma_exponential_case() {
+        // Q(1, x) = exp(-x), on both sides of the switch at x = 2
+        for &x in [1e-3, 0.5, 1.5, 1.999, 2., 2.5, 10., 50.].iter() {
+            assert_rel_close(upper_gamma_regularized(1., x), (-x).exp(), 1e-10);
+        }
+    }
+
+    #[test]
+    fn upper_gamma_regimes() {
+        // Q(1/2, x) = erfc(sqrt(x)), for small a and both small and large x
+        assert_rel_close(upper_gamma_regularized(0.5, 0.01), 0.887_537_083_981_715_2, 1e-10);
+        assert_rel_close(upper_gamma_regularized(0.5, 1.), 0.157_299_207_050_285_13, 1e-10);
+        assert_rel_close(upper_gamma_regularized(0.5, 4.), 4.677_734_981_047_266e-3, 1e-10);
+        assert_rel_close(upper_gamma_regularized(0.5, 25.), 1.537_459_794_428_035e-12, 1e-8);
+
+        // closed form for integer a, around the switch at x = a + 1
+        for &n in [2, 5, 12].iter() {
+            for &dx in [-0.5, -1e-9, 0., 1e-9, 0.5, 20.].iter() {
+                let x = n as f64 + 1. + dx;
+                assert_rel_close(
+                    upper_gamma_regularized(n as f64, x), upper_gamma_integer(n, x), 1e-10);
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Uniform};
+
+use pointprocesses::{TemporalProcess, PoissonProcess, VariablePoissonProcess};
+use pointprocesses::compoundpoisson::CompoundPoisson;
+
+
+/// Check that the mean of $S_T$ over seeded samples matches Wald's identity
+/// $\mathbb{E}[S_T] = \mathbb{E}[N_T]\mathbb{E}[Y]$, within four standard
+/// errors given by $\mathrm{Var}(S_T) = \mathbb{E}[N_T]\mathbb{E}[Y^2]$.
+fn assert_mean_sum<P, D>(
+    model: &CompoundPoisson<P, D>, tmax: f64, expected_count: f64,
+    (mean_jump, second_moment): (f64, f64), seed: u64)
+where P: TemporalProcess, D: Distribution<f64>
+{
+    let num_samples = 2000;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut total = 0.;
+    for _ in 0..num_samples {
+        let res = model.sample_with_rng(tmax, 11, &mut rng);
+        // the path on the grid agrees with the jumps
+        for (t, s) in res.times.iter().zip(res.path.iter()) {
+            assert!((res.value_at(*t) - s).abs() < 1e-12);
+        }
+        let sum = res.jumps.sum();
+        assert!((res.path[10] - sum).abs() < 1e-12);
+        total += sum;
+    }
+    let mean = total / num_samples as f64;
+    let expected = expected_count * mean_jump;
+    let std_err = (expected_count * second_moment / num_samples as f64).sqrt();
+    assert!((mean - expected).abs() < 4. * std_err,
+            "mean sum {} vs expected {}", mean, expected);
+}
+
+/// Moments of the jumps, uniform on $[0, 2]$.
+const UNIFORM_MOMENTS: (f64, f64) = (1., 4. / 3.);
+
+#[test]
+fn constant_intensity_mean_sum() {
+    let model = CompoundPoisson::new(PoissonProcess::new(3.), Uniform::new(0., 2.));
+    assert_mean_sum(&model, 2., 6., UNIFORM_MOMENTS, 31);
+}
+
+#[test]
+fn variable_intensity_mean_sum() {
+    // the intensity 2t integrates to 4 over [0, 2]
+    let model = CompoundPoisson::new(VariablePoissonProcess::new(|t| 2. * t, 4.), Uniform::new(0., 2.));
+    assert_mean_sum(&model, 2., 4., UNIFORM_MOMENTS, 37);
+}
@@ -0,0 +1,45 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::{TemporalProcess, PointProcessError};
+use pointprocesses::cox::LognormalCox;
+
+
+/// An empty window yields an empty path rather than NaN values,
+/// and invalid windows are rejected.
+#[test]
+fn empty_window() {
+    let model = LognormalCox::new(0.5, 1., 0.1);
+    let mut rng = StdRng::seed_from_u64(0);
+    let res = model.sample_path_with_rng(0., &mut rng);
+    assert_eq!(res.events.timestamps.len(), 0);
+    assert_eq!(res.times.len(), 1);
+    assert_eq!(res.intensity[0], 0.5_f64.exp());
+
+    let events = model.try_sample_with_rng(0., &mut rng).unwrap();
+    assert_eq!(events.timestamps.len(), 0);
+    assert!(matches!(
+        model.try_sample_with_rng(-1., &mut rng),
+        Err(PointProcessError::InvalidParameter(_))));
+}
+
+/// Without noise the intensity is constant, and the mean event count
+/// is that of a Poisson process.
+#[test]
+fn deterministic_intensity_count() {
+    let model = LognormalCox::new(1., 0., 0.1);
+    let tmax = 5.;
+    let num_samples = 2000;
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut total = 0;
+    for _ in 0..num_samples {
+        let res = model.sample_path_with_rng(tmax, &mut rng);
+        assert!(res.intensity.iter().all(|l| (l - 1_f64.exp()).abs() < 1e-12));
+        assert!(res.events.timestamps.iter().all(|t| (0. ..tmax).contains(t)));
+        total += res.events.timestamps.len();
+    }
+    let mean = total as f64 / num_samples as f64;
+    let expected = 1_f64.exp() * tmax;
+    let std_err = (expected / num_samples as f64).sqrt();
+    assert!((mean - expected).abs() < 4. * std_err, "mean count {} vs expected {}", mean, expected);
+}
@@ -0,0 +1,114 @@
+use ndarray::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand_distr::Exp1;
+
+use pointprocesses::{TemporalProcess, PoissonProcess, VariablePoissonProcess};
+use pointprocesses::hawkes::ExpHawkes;
+use pointprocesses::diagnostics::*;
+
+
+/// Quantiles of $\mathrm{Exp}(1)$ at the plotting positions $(i - 1/2)/n$.
+fn exp_quantiles(n: usize) -> Array1<f64> {
+    Array1::from_shape_fn(n, |i| -(1. - (i as f64 + 0.5) / n as f64).ln())
+}
+
+/// The compensators of Poisson processes are their integrated intensities.
+#[test]
+fn poisson_compensators() {
+    let times = arr1(&[0.5, 1.25, 3., 4.5]);
+
+    let increments = PoissonProcess::new(2.).compensator_increments(times.view());
+    let expected = arr1(&[1., 1.5, 3.5, 3.]);
+    assert!(increments.iter().zip(expected.iter()).all(|(a, b)| (a - b).abs() < 1e-12));
+
+    // the compensator of the intensity 2t is t^2, integrated exactly by Simpson's rule
+    let model = VariablePoissonProcess::new(|t| 2. * t, 10.);
+    let increments = model.compensator_increments(times.view());
+    let mut last = 0.;
+    for (incr, t) in increments.iter().zip(times.iter()) {
+        assert!((incr - (t * t - last * last)).abs() < 1e-10);
+        last = *t;
+    }
+}
+
+/// The closed-form compensator of the exponential Hawkes process matches
+/// the integral of its intensity, summed over the past events.
+#[test]
+fn exp_hawkes_compensator() {
+    let (alpha, beta, lbda0) = (0.8, 2., 1.);
+    let model = ExpHawkes::new(alpha, beta, lbda0);
+    let times = arr1(&[0.3, 0.4, 1.5, 1.6, 4.]);
+    let increments = model.compensator_increments(times.view());
+
+    // Lambda(t) = lbda0 t + sum_{t_j < t} alpha/beta (1 - exp(-beta (t - t_j)))
+    let compensator = |t: f64| {
+        times.iter()
+            .filter(|tj| **tj < t)
+            .fold(lbda0 * t, |acc, tj| acc + alpha / beta * (1. - (-beta * (t - tj)).exp()))
+    };
+    let mut last = 0.;
+    for (incr, t) in increments.iter().zip(times.iter()) {
+        let expected = compensator(*t) - compensator(last);
+        assert!((incr - expected).abs() < 1e-12, "{} vs {}", incr, expected);
+        last = *t;
+    }
+}
+
+#[test]
+fn ks_test_known_answers() {
+    // exactly Exp(1)-distributed data: the statistic is 1/(2n)
+    let n = 200;
+    let res = ks_test_exp(exp_quantiles(n).view());
+    assert!((res.statistic - 0.5 / n as f64).abs() < 1e-12);
+    assert!(res.p_value > 0.99);
+
+    // data with mean 3 is rejected
+    let res = ks_test_exp(exp_quantiles(n).mapv(|x| 3. * x).view());
+    assert!(res.p_value < 1e-6, "{:?}", res);
+}
+
+#[test]
+fn ljung_box_test_known_answers() {
+    let mut rng = StdRng::seed_from_u64(13);
+    let draws: Array1<f64> = (0..500).map(|_| rng.sample::<f64, _>(Exp1)).collect();
+    let res = ljung_box_test(draws.view(), 10);
+    assert!(res.p_value > 0.01, "{:?}", res);
+
+    // a strongly autocorrelated moving average of the draws
+    let smoothed = Array1::from_shape_fn(draws.len() - 4, |i| {
+        draws.iter().skip(i).take(5).sum::<f64>()
+    });
+    let res = ljung_box_test(smoothed.view(), 10);
+    assert!(res.p_value < 1e-6, "{:?}", res);
+
+    // constant data carries no autocorrelation
+    let res = ljung_box_test(Array1::ones(20).view(), 5);
+    assert_eq!(res.p_value, 1.);
+}
+
+#[test]
+fn qq_plot_known_answers() {
+    let data = arr1(&[2., 0.5, 1.]);
+    let qq = qq_plot_exp(data.view());
+    assert_eq!(qq.empirical, arr1(&[0.5, 1., 2.]));
+    assert!(qq.theoretical.iter().zip(exp_quantiles(3).iter()).all(|(a, b)| (a - b).abs() < 1e-12));
+}
+
+/// The diagnostics of a simulated sequence against the true model pass.
+#[test]
+fn diagnostics_accept_the_true_model() {
+    let model = ExpHawkes::new(0.8, 2., 1.);
+    let mut rng = StdRng::seed_from_u64(21);
+    let events = model.sample_with_rng(500., &mut rng);
+    let res = try_time_rescaling_diagnostics(&model, events.timestamps.view(), 10).unwrap();
+    assert_eq!(res.increments.len(), events.timestamps.len());
+    assert!(res.ks_test.p_value > 0.01, "{:?}", res.ks_test);
+    assert!(res.ljung_box_test.p_value > 0.01, "{:?}", res.ljung_box_test);
+
+    // a Poisson model with the same mean rate misses the clustering
+    let rate = events.timestamps.len() as f64 / 500.;
+    let res = time_rescaling_diagnostics(
+        &PoissonProcess::new(rate), events.timestamps.view(), 10);
+    assert!(res.ks_test.p_value < 0.01, "{:?}", res.ks_test);
+}
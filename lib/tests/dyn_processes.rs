@@ -0,0 +1,30 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::{TemporalProcess, PoissonProcess};
+use pointprocesses::hawkes::ExpHawkes;
+use pointprocesses::marked::MarkedPoissonProcess;
+use pointprocesses::traits::MarkedTemporalProcess;
+use rand_distr::Exp1;
+
+
+/// The sampling traits can be used as trait objects, and seeded
+/// sampling through a trait object is reproducible.
+#[test]
+fn processes_are_dyn_compatible() {
+    let models: Vec<Box<dyn TemporalProcess>> = vec![
+        Box::new(PoissonProcess::new(2.)),
+        Box::new(ExpHawkes::new(0.5, 1., 1.)),
+    ];
+    for model in models.iter() {
+        let first = model.sample_with_rng(20., &mut StdRng::seed_from_u64(3));
+        let second = model.sample_with_rng(20., &mut StdRng::seed_from_u64(3));
+        assert!(!first.timestamps.is_empty());
+        assert_eq!(first.timestamps, second.timestamps);
+    }
+
+    let marked: Box<dyn MarkedTemporalProcess<f64>> =
+        Box::new(MarkedPoissonProcess::new(PoissonProcess::new(2.), Exp1));
+    let events = marked.sample(20.);
+    assert_eq!(events.timestamps.len(), events.marks.len());
+}
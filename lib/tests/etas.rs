@@ -0,0 +1,31 @@
+use ndarray::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::PointProcessError;
+use pointprocesses::etas::Etas;
+use pointprocesses::likelihood::etas_likelihood;
+
+
+/// Catalogs without one magnitude per event are reported as errors.
+#[test]
+fn mismatched_magnitudes_are_rejected() {
+    let model = Etas::try_new(0.5, 0.02, 0.01, 1.2, 1., 1., 3.).unwrap();
+    let catalog = model.sample_catalog_with_rng(50., &mut StdRng::seed_from_u64(11));
+    let times = catalog.timestamps.view();
+    let magnitudes = catalog.magnitudes.view();
+
+    let llhood = etas_likelihood(times, magnitudes, &model, 50.).unwrap();
+    assert!(llhood.is_finite());
+    let declustering = model.decluster(times, magnitudes).unwrap();
+    assert_eq!(declustering.parents.len(), times.len());
+
+    let truncated: Array1<f64> = magnitudes.iter().skip(1).cloned().collect();
+    let truncated = truncated.view();
+    assert!(matches!(
+        etas_likelihood(times, truncated, &model, 50.),
+        Err(PointProcessError::InvalidData(_))));
+    assert!(matches!(
+        model.decluster(times, truncated),
+        Err(PointProcessError::InvalidData(_))));
+}
@@ -0,0 +1,90 @@
+use ndarray::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::{TemporalProcess, DeterministicIntensity, PointProcessError};
+use pointprocesses::hawkes::{ExpHawkes, Kernel};
+use pointprocesses::estimators::em::*;
+
+
+fn simulate(tmax: f64, seed: u64) -> Array1<f64> {
+    let model = ExpHawkes::new(0.8, 2.0, 1.0);
+    let mut rng = StdRng::seed_from_u64(seed);
+    model.sample_with_rng(tmax, &mut rng).timestamps
+}
+
+/// EM recovers the parameters of a simulated exponential Hawkes process.
+#[test]
+fn em_recovers_exp_hawkes_parameters() {
+    let tmax = 800.;
+    let times = simulate(tmax, 3);
+    let res = em_exp_hawkes(times.view(), tmax, &EmOptions::default()).unwrap();
+    assert!(res.converged);
+
+    let kernel = res.model.get_kernel();
+    let fitted = [res.model.get_background().intensity(0.), kernel.alpha, kernel.beta];
+    let truth = [1.0, 0.8, 2.0];
+    for k in 0..3 {
+        let err = (fitted[k] - truth[k]).abs() / truth[k];
+        assert!(err < 0.2, "fitted {:?} vs true {:?}", fitted, truth);
+    }
+
+    // a fraction 1 - alpha/beta of the events are immigrants
+    assert_eq!(res.background_probs.len(), times.len());
+    let immigrant_frac = res.background_probs.sum() / times.len() as f64;
+    assert!((immigrant_frac - 0.6).abs() < 0.1, "{}", immigrant_frac);
+}
+
+/// The nonparametric kernel carries the branching ratio of the process.
+#[test]
+fn em_nonparametric_recovers_branching_ratio() {
+    let tmax = 800.;
+    let times = simulate(tmax, 5);
+    let edges: Vec<f64> = (0..=6).map(|k| k as f64 * 0.5).collect();
+    let opts = EmOptions { max_iter: 200, tol: 1e-5 };
+    let res = em_nonparametric_hawkes(times.view(), tmax, &edges, &opts).unwrap();
+    let ratio = res.model.get_kernel().l1_norm();
+    assert!((ratio - 0.4).abs() < 0.08, "{}", ratio);
+}
+
+#[test]
+fn em_rejects_invalid_input() {
+    let empty = Array1::<f64>::zeros(0);
+    let opts = EmOptions::default();
+    assert!(matches!(
+        em_exp_hawkes(empty.view(), 10., &opts),
+        Err(PointProcessError::InvalidData(_))));
+
+    let times = simulate(50., 1);
+    assert!(matches!(
+        em_nonparametric_hawkes(times.view(), 50., &[], &opts),
+        Err(PointProcessError::InvalidParameter(_))));
+    assert!(matches!(
+        em_sumexp_hawkes(times.view(), 50., &[], &opts),
+        Err(PointProcessError::InvalidParameter(_))));
+}
+
+/// The branching probabilities of each event and its background
+/// probability sum to 1, and every parent precedes its offspring.
+#[test]
+fn em_branching_rows_sum_to_one() {
+    let tmax = 200.;
+    let times = simulate(tmax, 9);
+    let opts = EmOptions::default();
+    let edges: Vec<f64> = (0..=4).map(|k| k as f64 * 0.5).collect();
+
+    let exp_res = em_exp_hawkes(times.view(), tmax, &opts).unwrap();
+    let np_res = em_nonparametric_hawkes(times.view(), tmax, &edges, &opts).unwrap();
+    let results = [
+        (exp_res.background_probs, exp_res.parent_probs),
+        (np_res.background_probs, np_res.parent_probs)
+    ];
+    for (background_probs, parent_probs) in results.iter() {
+        assert_eq!(parent_probs.len(), times.len());
+        for (i, parents) in parent_probs.iter().enumerate() {
+            assert!(parents.iter().all(|&(j, p)| j < i && p >= 0.));
+            let total = parents.iter().fold(background_probs[i], |acc, (_, p)| acc + p);
+            assert!((total - 1.).abs() < 1e-10, "event {}: {}", i, total);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use ndarray::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::TemporalProcess;
+use pointprocesses::hawkes::{ExpHawkes, SumExpHawkes, SelfCorrecting};
+use pointprocesses::likelihood::{HawkesLikelihood, Likelihood};
+
+
+fn log_likelihood(times: ArrayView1<f64>, params: [f64; 3], tmax: f64) -> f64 {
+    HawkesLikelihood::new(times, params[0], params[1], params[2], tmax)
+        .compute_likelihood()
+}
+
+/// Compare the analytic gradient to central finite differences
+/// of the log-likelihood, for each of the three parameters.
+#[test]
+fn grad_matches_finite_differences() {
+    let tmax = 50.;
+    let model = ExpHawkes::new(0.6, 1.2, 0.8);
+    let mut rng = StdRng::seed_from_u64(0);
+    let events = model.sample_with_rng(tmax, &mut rng);
+    let times = events.timestamps.view();
+    assert!(times.len() > 10);
+
+    let param_points = [
+        [0.8, 0.6, 1.2],
+        [0.3, 1.5, 2.5],
+        [2.0, 0.1, 0.4],
+    ];
+
+    for params in param_points.iter() {
+        let grad = HawkesLikelihood::new(
+            times, params[0], params[1], params[2], tmax).grad();
+
+        for k in 0..3 {
+            let h = 1e-6 * params[k];
+            let mut up = *params;
+            let mut down = *params;
+            up[k] += h;
+            down[k] -= h;
+            let fd = (log_likelihood(times, up, tmax)
+                - log_likelihood(times, down, tmax)) / (2. * h);
+            let err = (grad[k] - fd).abs() / fd.abs().max(1.);
+            assert!(
+                err < 1e-5,
+                "component {} at {:?}: analytic {} vs finite difference {}",
+                k, params, grad[k], fd);
+        }
+    }
+}
+
+/// Compare the analytic gradient of the self-correcting log-likelihood
+/// to central finite differences, including a growth rate $\nu$ small
+/// enough to use the series expansion.
+#[test]
+fn self_correcting_grad_matches_finite_differences() {
+    let tmax = 30.;
+    let model = SelfCorrecting::new(0., 0.5, 0.4);
+    let mut rng = StdRng::seed_from_u64(1);
+    let events = model.sample_with_rng(tmax, &mut rng);
+    let times = events.timestamps.view();
+    assert!(times.len() > 10);
+
+    let param_points = [
+        [0., 0.5, 0.4],
+        [-1., 1e-6, 0.05],
+        [0.5, -0.1, 0.],
+    ];
+
+    for params in param_points.iter() {
+        let log_likelihood = |p: [f64; 3]| {
+            SelfCorrecting::new(p[0], p[1], p[2]).log_likelihood(times, tmax)
+        };
+        let grad = SelfCorrecting::new(params[0], params[1], params[2])
+            .grad(times, tmax).unwrap();
+
+        for k in 0..3 {
+            let h = 1e-6 * params[k].abs().max(1.);
+            let mut up = *params;
+            let mut down = *params;
+            up[k] += h;
+            down[k] -= h;
+            let fd = (log_likelihood(up) - log_likelihood(down)) / (2. * h);
+            let err = (grad[k] - fd).abs() / fd.abs().max(1.);
+            assert!(
+                err < 1e-5,
+                "component {} at {:?}: analytic {} vs finite difference {}",
+                k, params, grad[k], fd);
+        }
+    }
+}
+
+/// Sum-of-exponentials model with parameters
+/// $(\lambda_0, \alpha_1,\ldots,\alpha_p, \beta_1,\ldots,\beta_p)$.
+fn sumexp_model(params: &[f64]) -> SumExpHawkes {
+    let p = (params.len() - 1) / 2;
+    SumExpHawkes::new(params[1..=p].to_vec(), params[p + 1..].to_vec(), params[0])
+}
+
+/// Compare the analytic gradient of the sum-of-exponentials log-likelihood
+/// to central finite differences.
+#[test]
+fn sumexp_grad_matches_finite_differences() {
+    let tmax = 50.;
+    let model = sumexp_model(&[0.8, 0.3, 0.2, 1., 4.]);
+    let mut rng = StdRng::seed_from_u64(2);
+    let events = model.sample_with_rng(tmax, &mut rng);
+    let times = events.timestamps.view();
+    assert!(times.len() > 10);
+
+    let param_points = [
+        [0.8, 0.3, 0.2, 1., 4.],
+        [0.4, 0.05, 1.2, 0.3, 2.5],
+    ];
+
+    for params in param_points.iter() {
+        let grad = sumexp_model(params).grad(times, tmax).unwrap();
+        assert_eq!(grad.len(), params.len());
+
+        for k in 0..params.len() {
+            let h = 1e-6 * params[k];
+            let mut up = *params;
+            let mut down = *params;
+            up[k] += h;
+            down[k] -= h;
+            let fd = (sumexp_model(&up).log_likelihood(times, tmax)
+                - sumexp_model(&down).log_likelihood(times, tmax)) / (2. * h);
+            let err = (grad[k] - fd).abs() / fd.abs().max(1.);
+            assert!(
+                err < 1e-5,
+                "component {} at {:?}: analytic {} vs finite difference {}",
+                k, params, grad[k], fd);
+        }
+    }
+}
+
+/// With a single exponential, the sum-of-exponentials likelihood and its
+/// gradient reduce to those of the exponential model.
+#[test]
+fn single_term_sumexp_matches_exp_hawkes() {
+    let tmax = 50.;
+    let (lbda0, alpha, beta) = (0.8, 0.6, 1.2);
+    let model = ExpHawkes::new(alpha, beta, lbda0);
+    let mut rng = StdRng::seed_from_u64(3);
+    let events = model.sample_with_rng(tmax, &mut rng);
+    let times = events.timestamps.view();
+
+    let sumexp = SumExpHawkes::new(vec![alpha], vec![beta], lbda0);
+    let expected = model.log_likelihood(times, tmax);
+    let llhood = sumexp.log_likelihood(times, tmax);
+    assert!((llhood - expected).abs() < 1e-9 * expected.abs().max(1.),
+            "{} vs {}", llhood, expected);
+
+    let grad = sumexp.grad(times, tmax).unwrap();
+    let expected = model.grad(times, tmax).unwrap();
+    for (g, e) in grad.iter().zip(expected.iter()) {
+        assert!((g - e).abs() < 1e-9 * e.abs().max(1.), "{} vs {}", g, e);
+    }
+}
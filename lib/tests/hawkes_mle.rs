@@ -0,0 +1,104 @@
+use ndarray::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::{TemporalProcess, DeterministicIntensity, PoissonProcess, PointProcessError};
+use pointprocesses::hawkes::ExpHawkes;
+use pointprocesses::estimators::mle::{fit_exp_hawkes, fit_exp_hawkes_multi, FitOptions};
+
+
+/// Fitted parameters $(\lambda_0, \alpha, \beta)$ of the model.
+fn params(model: &ExpHawkes) -> [f64; 3] {
+    let kernel = model.get_kernel();
+    [model.get_background().intensity(0.), kernel.alpha, kernel.beta]
+}
+
+fn assert_close(fitted: [f64; 3], truth: [f64; 3], rel_tol: f64) {
+    for k in 0..3 {
+        let err = (fitted[k] - truth[k]).abs() / truth[k];
+        assert!(
+            err < rel_tol,
+            "component {}: fitted {:?} vs true {:?}", k, fitted, truth);
+    }
+}
+
+/// The estimator recovers the parameters of a long simulated sequence.
+#[test]
+fn mle_recovers_exp_hawkes_parameters() {
+    let truth = [1.0, 0.8, 2.0];
+    let tmax = 3000.;
+    let model = ExpHawkes::new(truth[1], truth[2], truth[0]);
+    let mut rng = StdRng::seed_from_u64(42);
+    let events = model.sample_with_rng(tmax, &mut rng);
+
+    let res = fit_exp_hawkes(events.timestamps.view(), tmax, &FitOptions::default()).unwrap();
+    assert!(res.converged);
+    assert_close(params(&res.model), truth, 0.15);
+}
+
+/// Pooling independent sequences recovers the parameters as well.
+#[test]
+fn pooled_mle_recovers_exp_hawkes_parameters() {
+    let truth = [0.5, 1.2, 3.0];
+    let tmax = 300.;
+    let model = ExpHawkes::new(truth[1], truth[2], truth[0]);
+    let mut rng = StdRng::seed_from_u64(7);
+    let samples: Vec<Array1<f64>> = (0..10)
+        .map(|_| model.sample_with_rng(tmax, &mut rng).timestamps)
+        .collect();
+    let sequences: Vec<(ArrayView1<f64>, f64)> = samples.iter()
+        .map(|times| (times.view(), tmax))
+        .collect();
+
+    let res = fit_exp_hawkes_multi(&sequences, &FitOptions::default()).unwrap();
+    assert!(res.converged);
+    assert_close(params(&res.model), truth, 0.15);
+}
+
+/// On data without self-excitation, and from an infeasible initial guess,
+/// the fitted parameters stay positive.
+#[test]
+fn mle_respects_positivity() {
+    let tmax = 1000.;
+    let mut rng = StdRng::seed_from_u64(1);
+    let events = PoissonProcess::new(2.).sample_with_rng(tmax, &mut rng);
+
+    let options = FitOptions {
+        init: Some([-1., -0.5, -2.]),
+        ..FitOptions::default()
+    };
+    let res = fit_exp_hawkes(events.timestamps.view(), tmax, &options).unwrap();
+    let fitted = params(&res.model);
+    assert!(fitted.iter().all(|p| p.is_finite() && *p > 0.), "{:?}", fitted);
+    assert!(res.log_likelihood.is_finite());
+    // the background rate carries the events
+    assert!((fitted[0] - 2.).abs() < 0.3, "{:?}", fitted);
+    assert!(fitted[1] / fitted[2] < 0.1, "{:?}", fitted);
+}
+
+/// Empty, unsorted and out-of-window sequences are rejected
+/// instead of being reported as converged fits.
+#[test]
+fn mle_rejects_invalid_data() {
+    let options = FitOptions::default();
+    let empty = Array1::<f64>::zeros(0);
+    assert!(matches!(
+        fit_exp_hawkes(empty.view(), 10., &options),
+        Err(PointProcessError::InvalidData(_))));
+    assert!(matches!(
+        fit_exp_hawkes_multi(&[(empty.view(), 10.), (empty.view(), 5.)], &options),
+        Err(PointProcessError::InvalidData(_))));
+
+    let unsorted = arr1(&[1., 3., 2.]);
+    assert!(matches!(
+        fit_exp_hawkes(unsorted.view(), 10., &options),
+        Err(PointProcessError::InvalidData(_))));
+
+    let sorted = arr1(&[1., 2., 3.]);
+    assert!(matches!(
+        fit_exp_hawkes(sorted.view(), 2.5, &options),
+        Err(PointProcessError::InvalidData(_))));
+    assert!(matches!(
+        fit_exp_hawkes(sorted.view(), 0., &options),
+        Err(PointProcessError::InvalidParameter(_))));
+}
@@ -0,0 +1,87 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::{TemporalProcess, PointProcessError, PiecewisePoissonProcess};
+use pointprocesses::hawkes::*;
+
+
+/// Check that the mean event count of seeded samples of the model matches
+/// its expected count, within four standard errors.
+fn assert_mean_count<K: Kernel>(model: &Hawkes<ConstBackground, K>, tmax: f64, seed: u64) {
+    let num_samples = 400;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let counts: Vec<f64> = (0..num_samples)
+        .map(|_| model.sample_with_rng(tmax, &mut rng).timestamps.len() as f64)
+        .collect();
+    let mean = counts.iter().sum::<f64>() / num_samples as f64;
+    let var = counts.iter().fold(0., |acc, c| acc + (c - mean).powi(2)) / (num_samples - 1) as f64;
+    let std_err = (var / num_samples as f64).sqrt();
+
+    let expected = model.expected_count(tmax);
+    assert!((mean - expected).abs() < 4. * std_err,
+            "mean count {} vs expected {} (std. error {})", mean, expected, std_err);
+}
+
+/// Ogata's thinning algorithm samples the power-law and tabulated kernels
+/// with the expected number of events.
+#[test]
+fn ogata_sampler_matches_expected_count() {
+    let model = PowerLawHawkes::new(0.5, 2., 1., 1.);
+    assert_mean_count(&model, 20., 17);
+
+    let kernel = TabulatedKernel::new(vec![0., 1., 2.], vec![0.6, 0.2, 0.], Interpolation::Linear);
+    let model = TabulatedHawkes::new(kernel, 1.);
+    assert_mean_count(&model, 20., 19);
+
+    let kernel = PiecewiseConstKernel::new(vec![0., 0.5, 1.5], vec![0.4, 0.3]);
+    let model = Hawkes::with_kernel(ConstBackground::new(1.), kernel);
+    assert_mean_count(&model, 20., 23);
+}
+
+/// The piecewise Poisson sampler draws the expected number of events,
+/// the integrated intensity over the window.
+#[test]
+fn piecewise_poisson_sampler_matches_expected_count() {
+    let nodes = vec![0., 2., 5., 8.];
+    let values = vec![1., 4., 0.5, 2.];
+    for &interpolation in [Interpolation::Constant, Interpolation::Linear].iter() {
+        let process = PiecewisePoissonProcess::new(nodes.clone(), values.clone(), interpolation);
+        let tmax = 6.;
+        let num_samples = 2000;
+        let mut rng = StdRng::seed_from_u64(29);
+        let total: usize = (0..num_samples)
+            .map(|_| process.sample_with_rng(tmax, &mut rng).timestamps.len())
+            .sum();
+        let mean = total as f64 / num_samples as f64;
+        // the count is Poisson, with variance equal to its mean
+        let expected = process.integrated_intensity(tmax);
+        let std_err = (expected / num_samples as f64).sqrt();
+        assert!((mean - expected).abs() < 4. * std_err,
+                "mean count {} vs expected {}", mean, expected);
+    }
+}
+
+/// Kernel which is neither monotone nor bounded.
+struct Oscillating;
+
+impl Kernel for Oscillating {
+    fn eval(&self, t: f64) -> f64 {
+        0.5 * (-t).exp() * (1. + t.sin())
+    }
+
+    fn integral(&self, t: f64) -> f64 {
+        // antiderivative of e^{-s}(1 + sin s) / 2
+        let primitive = |s: f64| -0.5 * (-s).exp() * (1. + 0.5 * (s.sin() + s.cos()));
+        primitive(t) - primitive(0.)
+    }
+}
+
+/// Kernels without an upper bound are rejected before sampling.
+#[test]
+fn unbounded_kernel_is_rejected() {
+    let model = Hawkes::with_kernel(ConstBackground::new(1.), Oscillating);
+    let mut rng = StdRng::seed_from_u64(0);
+    assert!(matches!(
+        model.try_sample_with_rng(10., &mut rng),
+        Err(PointProcessError::InvalidParameter(_))));
+}
@@ -0,0 +1,47 @@
+use pointprocesses::{PointProcessError, DeterministicIntensity, PiecewisePoissonProcess};
+use pointprocesses::hawkes::{Kernel, PiecewiseConstKernel, TabulatedKernel, Interpolation};
+
+
+/// Invalid tables are reported as errors rather than panics.
+#[test]
+fn tabulated_kernels_reject_invalid_tables() {
+    let invalid = [
+        PiecewiseConstKernel::try_new(vec![0., 1.], vec![0.5, 0.2]).err(),
+        PiecewiseConstKernel::try_new(vec![0., 1., 1.], vec![0.5, 0.2]).err(),
+        PiecewiseConstKernel::try_new(vec![0., 1.], vec![f64::NAN]).err(),
+        PiecewiseConstKernel::try_new(vec![0.], vec![]).err(),
+        TabulatedKernel::try_new(vec![0.], vec![1.], Interpolation::Linear).err(),
+        TabulatedKernel::try_new(vec![0., 1.], vec![1.], Interpolation::Linear).err(),
+        TabulatedKernel::try_new(vec![1., 0.], vec![1., 0.], Interpolation::Constant).err(),
+    ];
+    for err in invalid.iter() {
+        assert!(matches!(err, Some(PointProcessError::InvalidParameter(_))), "{:?}", err);
+    }
+
+    let kernel = PiecewiseConstKernel::try_new(vec![0., 1., 2.], vec![0.5, 0.2]).unwrap();
+    assert!((kernel.l1_norm() - 0.7).abs() < 1e-12);
+    let tabulated = TabulatedKernel::from(kernel);
+    assert!((tabulated.l1_norm() - 0.7).abs() < 1e-12);
+    let kernel = TabulatedKernel::try_new(vec![0., 2.], vec![1., 0.], Interpolation::Linear).unwrap();
+    assert!((kernel.eval(1.) - 0.5).abs() < 1e-12);
+}
+
+/// Tabulated kernels and piecewise Poisson intensities built on the same
+/// table agree, as do their integrals.
+#[test]
+fn tabulated_kernel_matches_piecewise_intensity() {
+    let nodes = vec![0.5, 1., 2., 4.];
+    let values = vec![1., 3., 0.5, 0.];
+    for &interpolation in [Interpolation::Constant, Interpolation::Linear].iter() {
+        let kernel = TabulatedKernel::new(nodes.clone(), values.clone(), interpolation);
+        let process = PiecewisePoissonProcess::new(nodes.clone(), values.clone(), interpolation);
+        for &t in [0., 0.5, 0.75, 1.5, 3., 4., 5.].iter() {
+            assert_eq!(kernel.eval(t), process.intensity(t));
+            assert!((kernel.integral(t) - process.integrated_intensity(t)).abs() < 1e-12);
+        }
+    }
+    let kernel = TabulatedKernel::new(nodes, values, Interpolation::Linear);
+    // 0.5 * (1 + 3) / 2 + (3 + 0.5) / 2 + 2 * (0.5 + 0) / 2
+    assert!((kernel.l1_norm() - 3.25).abs() < 1e-12);
+    assert_eq!(kernel.upper_bound(1.5), Some(3. - 2.5 * 0.5));
+}
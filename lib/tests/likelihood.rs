@@ -0,0 +1,78 @@
+use ndarray::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::{TemporalProcess, PoissonProcess, VariablePoissonProcess};
+use pointprocesses::hawkes::ExpHawkes;
+use pointprocesses::renewal::{RenewalProcess, GammaInterArrival};
+use pointprocesses::likelihood::Likelihood;
+
+
+/// The Poisson log-likelihood is $N\ln\lambda - \lambda T$, and the
+/// other models reduce to it when their intensity is constant.
+#[test]
+fn constant_intensity_likelihoods() {
+    let times = arr1(&[0.3, 1.1, 1.7, 3.2, 4.]);
+    let tmax = 5.;
+    let lbda: f64 = 1.5;
+    let expected = 5. * lbda.ln() - lbda * tmax;
+
+    let variable = VariablePoissonProcess::new(move |_| lbda, lbda);
+    // exponential inter-arrival times
+    let renewal = RenewalProcess::new(GammaInterArrival::new(1., 1. / lbda));
+    let models: [&dyn Likelihood; 3] = [&PoissonProcess::new(lbda), &variable, &renewal];
+    for model in models.iter() {
+        let llhood = model.log_likelihood(times.view(), tmax);
+        assert!((llhood - expected).abs() < 1e-10, "{} vs {}", llhood, expected);
+    }
+
+    // the gradient vanishes at the maximum likelihood estimate N/T
+    let grad = PoissonProcess::new(1.).grad(times.view(), tmax).unwrap();
+    assert!(grad[0].abs() < 1e-12);
+    assert!(variable.grad(times.view(), tmax).is_none());
+}
+
+/// With intensity $2t$, the log-likelihood is $\sum_i \ln(2t_i) - T^2$.
+#[test]
+fn variable_intensity_likelihood() {
+    let times = arr1(&[0.3, 1.1, 1.7, 3.2, 4.]);
+    let tmax = 5.;
+    let model = VariablePoissonProcess::new(|t| 2. * t, 10.);
+    let expected = times.fold(0., |acc, t: &f64| acc + (2. * t).ln()) - tmax * tmax;
+    let llhood = model.log_likelihood(times.view(), tmax);
+    assert!((llhood - expected).abs() < 1e-10, "{} vs {}", llhood, expected);
+}
+
+/// The pooled log-likelihood and gradient of independent sequences are
+/// the sums over the sequences, each with its own observation window.
+#[test]
+fn pooled_likelihood_is_the_sum() {
+    let model = ExpHawkes::new(0.6, 1.2, 0.8);
+    let mut rng = StdRng::seed_from_u64(41);
+    let samples: Vec<(Array1<f64>, f64)> = (0..20)
+        .map(|i| {
+            let tmax = 5. + i as f64;
+            (model.sample_with_rng(tmax, &mut rng).timestamps, tmax)
+        })
+        .collect();
+    let sequences: Vec<(ArrayView1<f64>, f64)> = samples.iter()
+        .map(|(times, tmax)| (times.view(), *tmax))
+        .collect();
+
+    let expected = sequences.iter()
+        .fold(0., |acc, (times, tmax)| acc + model.log_likelihood(*times, *tmax));
+    let pooled = model.pooled_log_likelihood(&sequences);
+    assert!((pooled - expected).abs() < 1e-9 * expected.abs());
+
+    let expected = sequences.iter()
+        .fold(Array1::zeros(3), |acc, (times, tmax)| acc + model.grad(*times, *tmax).unwrap());
+    let pooled = model.pooled_grad(&sequences).unwrap();
+    assert!(pooled.iter().zip(expected.iter()).all(|(g, e)| (g - e).abs() < 1e-9 * e.abs().max(1.)));
+    assert!(model.pooled_grad(&[]).is_none());
+
+    // the pooled Poisson estimate is the total count over the total time
+    let num_events: usize = sequences.iter().map(|(times, _)| times.len()).sum();
+    let total_time: f64 = sequences.iter().map(|(_, tmax)| tmax).sum();
+    let grad = PoissonProcess::new(num_events as f64 / total_time).pooled_grad(&sequences).unwrap();
+    assert!(grad[0].abs() < 1e-9);
+}
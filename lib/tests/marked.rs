@@ -0,0 +1,51 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand_distr::{Exp1, Uniform};
+
+use pointprocesses::{PoissonProcess, MarkedTemporalProcess};
+use pointprocesses::hawkes::ExpHawkes;
+use pointprocesses::marked::{MarkedPoissonProcess, MarkedExpHawkes};
+
+
+/// Independent marks follow the mark distribution, one per event.
+#[test]
+fn marked_poisson_marks() {
+    let model = MarkedPoissonProcess::new(PoissonProcess::new(2.), Uniform::new(1., 3.));
+    let mut rng = StdRng::seed_from_u64(7);
+    let events = model.sample_with_rng(1000., &mut rng);
+    let marks: &Vec<f64> = &events.marks;
+    assert_eq!(marks.len(), events.timestamps.len());
+    assert!(marks.iter().all(|m| (1. ..3.).contains(m)));
+
+    // the uniform marks have mean 2 and variance 1/3
+    let mean = marks.iter().sum::<f64>() / marks.len() as f64;
+    let std_err = (1. / 3. / marks.len() as f64).sqrt();
+    assert!((mean - 2.).abs() < 4. * std_err, "mean mark {}", mean);
+}
+
+/// With marks independent of the past, the mean event count of the marked
+/// Hawkes process is that of the unmarked process with jump size
+/// $\alpha \mathbb{E}[\psi(m)]$.
+#[test]
+fn marked_hawkes_mean_count() {
+    let (alpha, beta, lambda0) = (0.25, 1.5, 1.);
+    let tmax = 20.;
+    // Exp(1) marks, with impact psi(m) = 2m of mean 2
+    let model = MarkedExpHawkes::new(alpha, beta, lambda0, Exp1, |m: &f64| 2. * m);
+    let num_samples = 400;
+    let mut rng = StdRng::seed_from_u64(11);
+    let counts: Vec<f64> = (0..num_samples)
+        .map(|_| {
+            let events = MarkedTemporalProcess::<f64>::sample_with_rng(&model, tmax, &mut rng);
+            assert_eq!(events.marks.len(), events.timestamps.len());
+            events.timestamps.len() as f64
+        })
+        .collect();
+    let mean = counts.iter().sum::<f64>() / num_samples as f64;
+    let var = counts.iter().fold(0., |acc, c| acc + (c - mean).powi(2)) / (num_samples - 1) as f64;
+    let std_err = (var / num_samples as f64).sqrt();
+
+    let expected = ExpHawkes::new(2. * alpha, beta, lambda0).expected_count(tmax);
+    assert!((mean - expected).abs() < 4. * std_err,
+            "mean count {} vs expected {} (std. error {})", mean, expected, std_err);
+}
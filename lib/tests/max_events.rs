@@ -0,0 +1,30 @@
+use ndarray::prelude::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand_distr::Exp1;
+
+use pointprocesses::marked::MarkedExpHawkes;
+use pointprocesses::multivariate::MultivariateExpHawkes;
+use pointprocesses::traits::{MarkedTemporalProcess, MarkedProcessResult};
+
+
+/// Explosive models stop sampling once the event cap is reached.
+#[test]
+fn explosive_samplers_stop_at_max_events() {
+    let max_events = 500;
+    let mut rng = StdRng::seed_from_u64(5);
+
+    let marked = MarkedExpHawkes::new(3., 1., 1., Exp1, |m: &f64| *m)
+        .with_max_events(max_events);
+    let events: MarkedProcessResult<f64> = marked.sample_with_rng(1e3, &mut rng);
+    assert_eq!(events.timestamps.len(), max_events);
+    assert_eq!(events.marks.len(), max_events);
+
+    let multi = MultivariateExpHawkes::new(
+        arr1(&[1., 1.]),
+        arr2(&[[1., 1.], [1., 1.]]),
+        arr2(&[[1., 1.], [1., 1.]])).with_max_events(max_events);
+    let events = multi.sample_with_rng(1e3, &mut rng);
+    assert_eq!(events.timestamps.len(), max_events);
+    assert_eq!(events.intensities.dim(), (max_events, 2));
+}
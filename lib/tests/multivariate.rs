@@ -0,0 +1,29 @@
+use ndarray::prelude::*;
+
+use pointprocesses::PointProcessError;
+use pointprocesses::multivariate::MultivariateExpHawkes;
+
+
+/// Mismatched shapes and out-of-range parameters are reported as errors.
+#[test]
+fn invalid_parameters_are_rejected() {
+    let mu = arr1(&[1., 0.5]);
+    let alpha = arr2(&[[0.2, 0.1], [0.3, 0.2]]);
+    let beta = arr2(&[[1., 1.], [2., 2.]]);
+    assert!(MultivariateExpHawkes::try_new(mu.clone(), alpha.clone(), beta.clone()).is_ok());
+
+    let invalid = [
+        (arr1(&[1.]), alpha.clone(), beta.clone()),
+        (mu.clone(), arr2(&[[0.2, 0.1]]), beta.clone()),
+        (mu.clone(), alpha.clone(), arr2(&[[1.]])),
+        (arr1(&[-1., 0.5]), alpha.clone(), beta.clone()),
+        (mu.clone(), arr2(&[[0.2, f64::NAN], [0.3, 0.2]]), beta.clone()),
+        (mu.clone(), alpha.clone(), arr2(&[[1., 0.], [2., 2.]])),
+        (Array1::zeros(0), Array2::zeros((0, 0)), Array2::zeros((0, 0)))
+    ];
+    for (mu, alpha, beta) in invalid.iter() {
+        assert!(matches!(
+            MultivariateExpHawkes::try_new(mu.clone(), alpha.clone(), beta.clone()),
+            Err(PointProcessError::InvalidParameter(_))));
+    }
+}
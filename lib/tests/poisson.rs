@@ -0,0 +1,32 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::{TemporalProcess, TimeChangePoissonProcess};
+
+
+/// The time-change sampler only uses the increments of the cumulative
+/// intensity, so an offset $\Lambda(0) \neq 0$ does not change the events.
+#[test]
+fn time_change_with_offset_cumulative() {
+    let tmax = 3.;
+    let num_samples = 2000;
+    // intensity 2 on [0, T], with Lambda(0) = 5
+    let expected = 2. * tmax;
+    let std_err = (expected / num_samples as f64).sqrt();
+
+    let with_inverse = TimeChangePoissonProcess::new(|t| 5. + 2. * t, |s| (s - 5.) / 2.);
+    let bisection = TimeChangePoissonProcess::from_cumulative(|t| 5. + 2. * t);
+    let models: [&dyn TemporalProcess; 2] = [&with_inverse, &bisection];
+    for model in models.iter() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut total = 0;
+        for _ in 0..num_samples {
+            let events = model.sample_with_rng(tmax, &mut rng);
+            assert!(events.timestamps.iter().all(|t| (0. ..=tmax).contains(t)));
+            total += events.timestamps.len();
+        }
+        let mean = total as f64 / num_samples as f64;
+        assert!((mean - expected).abs() < 4. * std_err,
+                "mean count {} vs expected {}", mean, expected);
+    }
+}
@@ -0,0 +1,71 @@
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use pointprocesses::TemporalProcess;
+use pointprocesses::renewal::*;
+
+
+/// Sample mean and its standard error.
+fn mean_and_std_err(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let var = values.iter().fold(0., |acc, v| acc + (v - mean).powi(2)) / (n - 1.);
+    (mean, (var / n).sqrt())
+}
+
+/// Check that the inter-arrival times of a long seeded sequence have the
+/// mean of the inter-arrival distribution, within four standard errors.
+fn assert_mean_inter_arrival<D: InterArrival>(dist: D, seed: u64) {
+    let expected = dist.mean();
+    let model = RenewalProcess::new(dist);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let times = model.sample_with_rng(10000. * expected, &mut rng).timestamps;
+    let gaps: Vec<f64> = times.iter()
+        .zip(times.iter().skip(1))
+        .map(|(a, b)| b - a)
+        .collect();
+    let (mean, std_err) = mean_and_std_err(&gaps);
+    assert!((mean - expected).abs() < 4. * std_err,
+            "mean inter-arrival {} vs expected {}", mean, expected);
+}
+
+#[test]
+fn mean_inter_arrival_times() {
+    assert_mean_inter_arrival(GammaInterArrival::new(2., 0.5), 1);
+    assert_mean_inter_arrival(WeibullInterArrival::new(1.5, 0.8), 2);
+    assert_mean_inter_arrival(LogNormalInterArrival::new(0., 0.5), 3);
+    // the shape must exceed 2 for the variance to be finite
+    assert_mean_inter_arrival(ParetoInterArrival::new(1., 3.), 4);
+}
+
+/// From a stationary start, the first arrival follows the equilibrium
+/// distribution, of mean $\mathbb{E}[X^2]/(2\mathbb{E}[X])$, and the mean
+/// count over $[0, T]$ is exactly $T/\mathbb{E}[X]$.
+#[test]
+fn stationary_start() {
+    let (shape, scale) = (2., 0.5);
+    // E[X] = 1 and E[X^2] = shape (shape + 1) scale^2
+    let first_arrival_mean = (shape + 1.) * scale / 2.;
+    let tmax = 10.;
+    let model = RenewalProcess::new(GammaInterArrival::new(shape, scale)).with_stationary_start();
+
+    let num_samples = 4000;
+    let mut rng = StdRng::seed_from_u64(5);
+    let mut first_arrivals = Vec::new();
+    let mut counts = Vec::new();
+    for _ in 0..num_samples {
+        let times = model.sample_with_rng(tmax, &mut rng).timestamps;
+        if let Some(t) = times.iter().next() {
+            first_arrivals.push(*t);
+        }
+        counts.push(times.len() as f64);
+    }
+    // a first arrival after T has probability below 1e-7
+    assert_eq!(first_arrivals.len(), num_samples);
+    let (mean, std_err) = mean_and_std_err(&first_arrivals);
+    assert!((mean - first_arrival_mean).abs() < 4. * std_err,
+            "mean first arrival {} vs expected {}", mean, first_arrival_mean);
+
+    let (mean, std_err) = mean_and_std_err(&counts);
+    assert!((mean - tmax).abs() < 4. * std_err, "mean count {} vs expected {}", mean, tmax);
+}
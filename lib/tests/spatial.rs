@@ -0,0 +1,19 @@
+use ndarray::prelude::*;
+
+use pointprocesses::PointProcessError;
+use pointprocesses::spatial::{Domain, try_poisson_process, try_variable_poisson};
+
+
+/// Zero-dimensional domains are reported as errors by the samplers.
+#[test]
+fn empty_domain_is_rejected() {
+    assert!(Domain::try_new(Array1::zeros(0), Array1::zeros(0)).is_err());
+
+    let domain = Domain::new(Array1::zeros(0), Array1::zeros(0));
+    assert!(matches!(
+        try_poisson_process(1., &domain),
+        Err(PointProcessError::InvalidParameter(_))));
+    assert!(matches!(
+        try_variable_poisson(|_: &Array1<f64>| 1., 1., &domain),
+        Err(PointProcessError::InvalidParameter(_))));
+}
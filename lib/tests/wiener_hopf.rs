@@ -0,0 +1,49 @@
+use ndarray::prelude::*;
+
+use pointprocesses::PointProcessError;
+use pointprocesses::estimators::wienerhopf::wiener_hopf_kernel;
+
+
+/// Timestamps on a regular grid give lags which are exact multiples of
+/// the bin width, at the edge of the estimated support.
+#[test]
+fn gridded_timestamps_stay_in_bounds() {
+    let tmax = 100.;
+    for (step, bin_width, num_bins) in [(0.1, 0.1, 17), (1., 1., 5), (0.1, 0.3, 7)] {
+        let num_events = (tmax / step) as usize;
+        let times: Array1<f64> = (0..num_events)
+            .filter(|i| i % 3 != 1)
+            .map(|i| i as f64 * step)
+            .collect();
+        let kernel = wiener_hopf_kernel(times.view(), tmax, bin_width, num_bins).unwrap();
+        assert_eq!(kernel.get_values().len(), num_bins);
+        assert!(kernel.get_values().iter().all(|v| v.is_finite()));
+    }
+}
+
+/// Invalid windows, bins and event times are reported as errors.
+#[test]
+fn invalid_input_is_rejected() {
+    let times = arr1(&[0.5, 1., 2.5, 3.]);
+    let invalid_params = [
+        (10., 0., 5),
+        (10., -0.1, 5),
+        (10., f64::NAN, 5),
+        (0., 0.1, 5),
+        (f64::INFINITY, 0.1, 5),
+        (10., 0.1, 0)
+    ];
+    for &(tmax, bin_width, num_bins) in invalid_params.iter() {
+        assert!(matches!(
+            wiener_hopf_kernel(times.view(), tmax, bin_width, num_bins),
+            Err(PointProcessError::InvalidParameter(_))));
+    }
+
+    let unsorted = arr1(&[1., 0.5]);
+    assert!(matches!(
+        wiener_hopf_kernel(unsorted.view(), 10., 0.1, 5),
+        Err(PointProcessError::InvalidData(_))));
+    assert!(matches!(
+        wiener_hopf_kernel(times.view(), 2., 0.1, 5),
+        Err(PointProcessError::InvalidData(_))));
+}